@@ -14,6 +14,304 @@ const Handlebars = {
     }
 }
 
+/**
+ * 工作表的使用区域，行列均从0开始。
+ *
+ * @typedef {Object} SheetDimensions
+ * @property {?Array<number>} start 左上角单元格 [行, 列]，空工作表为null。
+ * @property {?Array<number>} end 右下角单元格 [行, 列]，空工作表为null。
+ * @property {number} height 行数。
+ * @property {number} width 列数。
+ */
+
+/**
+ * 合并单元格区域，行列均从0开始。
+ *
+ * @typedef {Object} MergedRegion
+ * @property {string} range 区域引用，如 "A1:C2"。
+ * @property {Array<number>} start 左上角单元格 [行, 列]。
+ * @property {Array<number>} end 右下角单元格 [行, 列]。
+ */
+
+/**
+ * 工作表数据及元数据。
+ *
+ * @typedef {Object} Sheet
+ * @property {string} name 工作表名称。
+ * @property {number} index 工作表在工作簿中的序号，从0开始。
+ * @property {string} visible 可见性：visible、hidden 或 veryHidden。
+ * @property {string} sheetType 工作表类型：worksheet、chartsheet 等。
+ * @property {SheetDimensions} dimensions 工作表的使用区域。
+ * @property {Array<string>=} headers 表头，仅在设置 header 时返回。
+ * @property {Array<Object>} rows 行数据，每行是以列字母或表头为键的对象，公式单元格为缓存的计算结果。
+ * @property {Array<Object>=} formulas 仅在设置 formulas 时返回，与 rows 一一对应，
+ * 每行只包含有公式的单元格，值为公式文本（如 "=SUM(B2:B10)"）。
+ * @property {Array<MergedRegion>=} merged 合并单元格区域，仅在设置 mergedCells 时返回。
+ */
+
+/**
+ * 读取xls文件的选项。
+ *
+ * @typedef {Object} ReadOptions
+ * @property {(string|number)=} sheet 工作表名称或序号（从0开始），默认为 range 中的工作表或第一个工作表。
+ * @property {string=} range 单元格区域，如 "B2:F500"、"Sheet1!B2:F500"、"A:C"。
+ * @property {number=} skipRows 跳过区域顶部的行数，用于跳过表头上方的标题行，默认为0。
+ * @property {boolean=} header 为true时将跳过后的第一行作为表头，行对象以表头文字为键；
+ * 空表头使用列字母，重复表头依次加 _2、_3 后缀。默认为false。
+ * @property {string=} dates 日期、时间单元格的返回方式："iso" 返回ISO-8601字符串（如 "2024-03-01T08:30:00"），
+ * "date" 返回JS Date对象，"serial" 返回Excel序列号。时长单元格在 iso 和 date 模式下返回ISO-8601时长字符串（如 "PT1H30M"）。默认为 "iso"。
+ * @property {string=} errors 错误单元格（如 #DIV/0!）的返回方式："object" 返回 {error: "#DIV/0!"}，
+ * "string" 返回错误文本，"null" 返回null。默认为 "object"。
+ * @property {boolean=} formulas 为true时同时返回公式，见 Sheet.formulas。默认为false。
+ * @property {boolean=} mergedCells 为true时返回合并单元格区域，见 Sheet.merged。默认为false。
+ * @property {boolean=} fillMerged 为true时将合并区域左上角的值填充到区域内的每个单元格。默认为false。
+ * 注意：xlsb 和 ods 格式不支持读取合并单元格。
+ */
+
+/**
+ * 读取csv文件的选项，同时支持 ReadOptions 中的 range、skipRows 和 header。
+ *
+ * @typedef {Object} CsvOptions
+ * @property {string=} delimiter 分隔符，单个字符，如 ","、";"、"|"、"\t"。未设置时根据文件前几行自动识别，.tsv 文件使用制表符。
+ * @property {string=} quote 引号字符，默认为 "。
+ * @property {string=} encoding 文件编码，如 "utf-8"、"gbk"、"gb18030"、"utf-16le"。未设置或为 "auto" 时自动识别，带BOM的文件按BOM识别。
+ * @property {boolean=} raw 为true时所有字段均返回字符串。默认将数字和 TRUE/FALSE 转换为对应类型，
+ * 以0开头的编号（如 "007"）和超过15位的数字保持为字符串。
+ * @property {string=} range 单元格区域，如 "A1:C100"。
+ * @property {number=} skipRows 跳过顶部的行数。
+ * @property {boolean=} header 为true时将跳过后的第一行作为表头。
+ */
+
+/**
+ * 写入csv文件的选项。
+ *
+ * @typedef {Object} CsvWriteOptions
+ * @property {string=} delimiter 分隔符，单个字符，默认为 ","。
+ * @property {string=} quote 引号字符，默认为 "。包含分隔符、引号或换行的字段会自动加引号，字段中的引号写为两个引号。
+ * @property {string=} lineEnding 换行符，"crlf"（默认，与Excel一致）或 "lf"。
+ * @property {string=} encoding 文件编码，默认为 "utf-8-bom"，Excel可以直接打开不乱码。也可以是 "utf-8"、"gbk"、"gb18030"、"utf-16le"。
+ * @property {boolean=} quoteAll 为true时所有字段都加引号。
+ * @property {boolean=} append 为true时追加到文件末尾，不覆盖原有内容。
+ * @property {(boolean|Array<string>)=} header 行为对象时的表头，默认使用第一行对象的键作为表头并写入第一行；
+ * 传入数组时按数组的顺序写入对应的键，为false时不写表头。追加到已有文件时不写表头。
+ */
+
+/**
+ * 遍历目录的选项。通配符中 * 匹配任意字符，** 匹配任意层目录，? 匹配单个字符，{a,b} 匹配其中之一。
+ *
+ * @typedef {Object} WalkOptions
+ * @property {(string|Array<string>)=} include 要返回的文件的通配符，与相对于遍历目录的路径匹配，如 "*.xlsx"、"2024/**&#47;*.csv"。未设置时返回全部。
+ * @property {(string|Array<string>)=} exclude 要跳过的文件或目录的通配符，匹配的目录不会进入，如 "node_modules"、"**&#47;~$*"。
+ * @property {number=} maxDepth 最大深度，1 表示只返回目录的直接子项，未设置时不限制。
+ * @property {("file"|"dir")=} kind 只返回文件或只返回目录。
+ * @property {boolean=} followLinks 为true时进入符号链接指向的目录，脚本限制了可访问的目录时会抛出错误。
+ */
+
+/**
+ * 遍历目录得到的文件或目录。
+ *
+ * @typedef {Object} FileEntry
+ * @property {string} path 完整路径。
+ * @property {string} relative 相对于遍历目录的路径，使用 / 分隔。
+ * @property {string} name 文件名或目录名。
+ * @property {("file"|"dir"|"symlink")} type 类型。
+ * @property {number} size 文件大小（字节），目录为0。
+ * @property {Date|null} modified 修改时间。
+ */
+
+/**
+ * 文件或目录的信息。
+ *
+ * @typedef {Object} FileStat
+ * @property {string} path 路径。
+ * @property {("file"|"dir"|"symlink")} type 类型，符号链接返回其指向的文件或目录的类型，指向的文件不存在时为 "symlink"。
+ * @property {number} size 文件大小（字节），目录为0。
+ * @property {Date|null} created 创建时间，系统不支持时为null。
+ * @property {Date|null} modified 修改时间。
+ * @property {Date|null} accessed 访问时间。
+ * @property {boolean} readonly 是否只读。
+ * @property {boolean} symlink 路径本身是否是符号链接（快捷方式不是符号链接）。
+ */
+
+/**
+ * fs 的异步版本，每个方法返回Promise。
+ *
+ * @typedef {Object} FsPromises
+ * @property {function(string): Promise<void>} create 创建文件，若文件已存在则清空。
+ * @property {function(string, string, string=): Promise<void>} append 向文件追加文本，参数为路径、内容和编码。
+ * @property {function(string, string): Promise<void>} copy 复制文件。
+ * @property {function(string): Promise<void>} create_dir 创建单个目录。
+ * @property {function(string): Promise<Array<string>>} read_dir 读取目录下的文件和目录。
+ * @property {function(string): Promise<boolean>} is_dir 判断是否是目录。
+ * @property {function(string): Promise<boolean>} is_file 判断是否是文件。
+ * @property {function(string): Promise<void>} create_dir_all 递归创建目录。
+ * @property {function(string): Promise<boolean>} exists 检查文件或目录是否存在。
+ * @property {function(string, string): Promise<void>} hard_link 创建硬链接。
+ * @property {function(string, string=): Promise<string>} read_to_string 读取文件内容，参数为路径和编码。
+ * @property {function(string): Promise<void>} remove_dir 删除空目录。
+ * @property {function(string): Promise<void>} remove_dir_all 递归删除目录。
+ * @property {function(string): Promise<void>} remove_file 删除文件。
+ * @property {function(string, string): Promise<void>} rename 重命名或移动文件。
+ * @property {function(string, string, string=): Promise<void>} write 写入文本，覆盖原有内容，参数为路径、内容和编码。
+ * @property {function(string, string=): Promise<Array<string>>} read_to_line 逐行读取文件，参数为路径和编码。
+ * @property {function(string): Promise<Uint8Array>} read_bytes 读取文件的全部字节。
+ * @property {function(string, number, number): Promise<Uint8Array>} read_range 读取从 offset 开始的最多 length 个字节。
+ * @property {function(string, (Uint8Array|ArrayBuffer|Array<number>)): Promise<void>} write_bytes 将字节写入文件。
+ * @property {function(string, (Uint8Array|ArrayBuffer|Array<number>)): Promise<void>} append_bytes 追加字节到文件末尾。
+ * @property {function(string, WalkOptions=): Promise<Array<FileEntry>>} walk 递归遍历目录。
+ * @property {function(string, WalkOptions=): Promise<Array<FileEntry>>} glob 查找与通配符匹配的文件和目录。
+ * @property {function(string): Promise<FileStat>} stat 获取文件或目录的信息。
+ */
+
+/**
+ * Excel表格（插入 > 表格 创建的结构化表格）。
+ *
+ * @typedef {Object} TableInfo
+ * @property {string} name 表格名称，如 "Table1"。
+ * @property {string} sheet 表格所在的工作表名称。
+ * @property {Array<string>} columns 表格的列名，按从左到右的顺序排列。
+ * @property {?string} range 表格数据行（不含表头）的单元格区域，如 "B3:F120"，表格没有数据时为 null。
+ * @property {?Array<number>} start 数据区域左上角的 [行, 列]，从0开始，表格没有数据时为 null。
+ * @property {?Array<number>} end 数据区域右下角的 [行, 列]，从0开始，表格没有数据时为 null。
+ */
+
+/**
+ * 工作簿中定义的名称（公式 > 名称管理器）。
+ *
+ * @typedef {Object} DefinedName
+ * @property {string} name 名称，如 "SalesData"。
+ * @property {string} refersTo 引用位置，如 "Sheet1!$A$1:$C$10"。
+ */
+
+/**
+ * 工作簿对象，由 fs.read_workbook 返回。
+ *
+ * @typedef {Object} Workbook
+ * @property {Array<Sheet>} sheets 所有工作表，按工作簿中的顺序排列。
+ * @property {function(): Array<string>} sheet_names 返回所有工作表名称。
+ * @property {function((string|number)): (Sheet|undefined)} sheet 按名称或序号查找工作表。
+ */
+
+/**
+ * 路径操作api，只处理路径字符串，不访问文件，同时支持 / 和 \ 分隔的Windows路径。
+ * 返回的路径使用当前系统的分隔符。
+ */
+const path = {
+    /**
+     * 当前系统的路径分隔符，Windows 为 "\\"，其他系统为 "/"。
+     *
+     * @type {string}
+     */
+    sep: "\\",
+    /**
+     * 拼接路径并规范化，忽略空字符串。
+     *
+     * 例：path.join("C:/data", "2024", "../report.xlsx") 返回 "C:\\data\\report.xlsx"
+     *
+     * @param {...string} parts 路径片段。
+     * @return {string} 返回拼接后的路径。
+     */
+    join: function (...parts) {
+    },
+    /**
+     * 从程序的工作目录开始依次解析路径片段，返回绝对路径，绝对路径的片段会替换之前的结果。
+     *
+     * @param {...string} parts 路径片段。
+     * @return {string} 返回绝对路径。
+     */
+    resolve: function (...parts) {
+    },
+    /**
+     * 规范化路径，去掉 . 并解析 ..，分隔符转换为当前系统的分隔符。
+     *
+     * @param {string} path 路径。
+     * @return {string} 返回规范化后的路径，空路径返回 "."。
+     */
+    normalize: function (path) {
+    },
+    /**
+     * 获取路径所在的目录。
+     *
+     * 例：path.dirname("C:/data/report.xlsx") 返回 "C:/data"
+     *
+     * @param {string} path 文件或目录的路径。
+     * @return {string} 返回目录，只有文件名时返回 "."。
+     */
+    dirname: function (path) {
+    },
+    /**
+     * 获取路径的最后一部分，即文件名或目录名。
+     *
+     * @param {string} path 文件或目录的路径。
+     * @return {string} 返回文件名或目录名，如 "report.xlsx"。
+     */
+    basename: function (path) {
+    },
+    /**
+     * 获取文件的扩展名，包含点。
+     *
+     * @param {string} path 文件的路径。
+     * @return {string} 返回扩展名，如 ".xlsx"，没有扩展名时返回空字符串。
+     */
+    extname: function (path) {
+    },
+    /**
+     * 获取不包含扩展名的文件名。
+     *
+     * @param {string} path 文件的路径。
+     * @return {string} 返回文件名，如 "C:/data/report.xlsx" 返回 "report"。
+     */
+    stem: function (path) {
+    },
+    /**
+     * 获取从 from 到 to 的相对路径，不在同一个盘符时返回 to 的绝对路径。
+     *
+     * 例：path.relative("C:/data/2024", "C:/data/2023/a.xlsx") 返回 "..\\2023\\a.xlsx"
+     *
+     * @param {string} from 起始路径。
+     * @param {string} to 目标路径。
+     * @return {string} 返回相对路径。
+     */
+    relative: function (from, to) {
+    },
+    /**
+     * 获取当前用户的主目录，如 "C:\\Users\\name"。
+     *
+     * @return {string} 返回目录的路径。
+     */
+    home_dir: function () {
+    },
+    /**
+     * 获取当前用户的桌面目录。
+     *
+     * @return {string} 返回目录的路径。
+     */
+    desktop_dir: function () {
+    },
+    /**
+     * 获取当前用户的文档目录。
+     *
+     * @return {string} 返回目录的路径。
+     */
+    documents_dir: function () {
+    },
+    /**
+     * 获取当前用户的下载目录。
+     *
+     * @return {string} 返回目录的路径。
+     */
+    downloads_dir: function () {
+    },
+    /**
+     * 获取脚本的输出目录，未设置时为 ~/xlsDsl/output。
+     * 脚本只能读写模板文件所在的目录、输出目录和脚本设置中允许的目录，访问其他路径会抛出 PermissionDenied 错误。
+     *
+     * @return {string} 返回目录的路径。
+     */
+    output_dir: function () {
+    }
+}
+
 /**
  * 文件流操作api，提供一系列文件和目录的操作方法
  */
@@ -31,18 +329,117 @@ const fs = {
      * 
      * @param {string} filePath 文件的完整路径，包含文件名和扩展名。
      * @param {string} content  要追加到文件中的文本内容。
+     * @param {string=} encoding 文件编码，需与文件原有内容的编码一致，如 "utf-8"、"gbk"。可选参数，默认为 "utf-8"。
      * @return {void} 该方法没有返回值。
      */
-    append: function (filePath, content) {
+    append: function (filePath, content, encoding) {
     },
     /**
      * 异步读取xls文件内容，将文件中的数据以二维数组的形式返回。
+     * 支持 xls、xlsx、xlsm、xlsb 和 ods 格式，根据文件扩展名和文件头自动识别。
+     * 日期单元格返回ISO-8601字符串，错误单元格返回 {error: "#DIV/0!"}。
      * 
      * @param {string=} filePath 文件的完整路径，包含文件名和扩展名。可选参数，若未提供，则默认读取选择的文件。
      * @return {Promise<Array<Array>>} 一个Promise对象，解析为一个二维数组，代表xls文件中的数据。
      */
     read_xls: async function (filePath) {
     },
+    /**
+     * 异步读取xls文件的所有工作表，返回带有工作表名称和元数据的工作簿对象。
+     * 每个工作表的行对象以列字母（A、B、C...）为键，列字母与工作表中的实际列一致；
+     * 设置 header 后以表头文字为键。
+     *
+     * @param {string=} filePath 文件的完整路径，包含文件名和扩展名。可选参数，若未提供，则默认读取选择的文件。
+     * @param {ReadOptions=} options 读取选项，sheet 和 range 在此方法中被忽略。
+     * @return {Promise<Workbook>} 一个Promise对象，解析为工作簿对象。
+     */
+    read_workbook: async function (filePath, options) {
+    },
+    /**
+     * 异步读取xls文件中的单个工作表，可只读取指定的单元格区域，不会加载其他工作表。
+     *
+     * 例：fs.read_sheet(path, "Orders")、fs.read_sheet(path, 2)、
+     * fs.read_sheet(path, "Sheet1!B2:F500")、fs.read_sheet(path, {sheet: "Orders", range: "A:C", skipRows: 2, header: true})
+     *
+     * @param {string=} filePath 文件的完整路径，包含文件名和扩展名。传入空字符串或undefined时读取选择的文件。
+     * @param {(string|number|ReadOptions)=} options 工作表名称、序号（从0开始）、
+     * 带工作表名的区域（如 "Sheet1!B2:F500"）或选项对象。未指定工作表时读取第一个工作表。
+     * @return {Promise<Sheet>} 一个Promise对象，解析为工作表对象。
+     */
+    read_sheet: async function (filePath, options) {
+    },
+    /**
+     * 异步读取csv或tsv文件，支持引号、字段内换行、自定义分隔符和 GBK 等编码。
+     * 返回的工作表对象与 read_sheet 相同，工作表名称为文件名，空字段返回null。
+     *
+     * 例：fs.read_csv("C:/data/orders.csv", {header: true})、fs.read_csv(path, {delimiter: ";", encoding: "gbk"})
+     *
+     * @param {string=} filePath 文件的完整路径，包含文件名和扩展名。
+     * @param {CsvOptions=} options 读取选项。
+     * @return {Promise<Sheet>} 一个Promise对象，解析为工作表对象。
+     */
+    read_csv: async function (filePath, options) {
+    },
+    /**
+     * 将多行数据写入csv文件，若文件的文件夹不存在则创建。
+     * 每一行可以是数组，也可以是对象（按表头的键取值）；日期写为 yyyy-mm-dd hh:mm:ss，null和undefined写为空字段。
+     *
+     * 例：fs.write_csv("C:/out/orders.csv", [{id: 1, name: "a,b"}])、fs.write_csv(path, [[1, "x"]], {delimiter: ";", encoding: "gbk"})
+     *
+     * @param {string} filePath 文件的完整路径，包含文件名和扩展名。
+     * @param {Array<Array>|Array<Object>} rows 要写入的行。
+     * @param {CsvWriteOptions=} options 写入选项。
+     * @return {number} 返回写入的行数，包含表头。
+     */
+    write_csv: function (filePath, rows, options) {
+    },
+    /**
+     * 按名称读取Excel表格或定义的名称所引用的单元格区域，返回以列名为键的行对象。
+     * 先查找同名表格，找不到时再查找定义的名称，名称不区分大小写。
+     * 表格以表格列名为键，定义的名称以区域的第一行为表头。表格仅 xlsx 格式支持。
+     *
+     * 例：fs.read_table(path, "Orders")、fs.read_table("", "SalesData", {dates: "date"})
+     *
+     * @param {string=} filePath 文件的完整路径，包含文件名和扩展名。传入空字符串或undefined时读取选择的文件。
+     * @param {string} name 表格名称或定义的名称。
+     * @param {ReadOptions=} options 读取选项，sheet、range 和 header 在此方法中被忽略。
+     * @return {Promise<Sheet>} 一个Promise对象，解析为工作表对象，headers 为表格的列名。
+     */
+    read_table: async function (filePath, name, options) {
+    },
+    /**
+     * 列出xls文件中的所有Excel表格，非 xlsx 格式返回空数组。
+     *
+     * @param {string=} filePath 文件的完整路径，包含文件名和扩展名。传入空字符串或undefined时读取选择的文件。
+     * @return {Promise<Array<TableInfo>>} 一个Promise对象，解析为表格信息数组。
+     */
+    xls_tables: async function (filePath) {
+    },
+    /**
+     * 列出xls文件中定义的所有名称，包括 _xlnm._FilterDatabase 等Excel内置的隐藏名称。
+     *
+     * @param {string=} filePath 文件的完整路径，包含文件名和扩展名。传入空字符串或undefined时读取选择的文件。
+     * @return {Promise<Array<DefinedName>>} 一个Promise对象，解析为名称数组。
+     */
+    xls_names: async function (filePath) {
+    },
+    /**
+     * 逐行读取xls文件中的单个工作表，返回异步迭代器，适合读取几十万行的大文件。
+     * 行在后台线程中分批读取，脚本处理完一批后才会继续读取下一批。
+     * xlsx 文件按单元格流式解析，内存占用与文件大小无关；xls、xlsb 和 ods 文件仍需一次性加载整个工作表。
+     * 流式读取不支持 formulas、mergedCells 和 fillMerged 选项，设置这些选项时会抛出错误，请改用 read_sheet。
+     *
+     * 例：for await (const row of fs.stream_xls(path, {sheet: "Orders", header: true})) { ... }
+     *
+     * @param {string=} filePath 文件的完整路径，包含文件名和扩展名。传入空字符串或undefined时读取选择的文件。
+     * @param {(string|number|(ReadOptions & {chunkSize: (number|undefined), chunks: (boolean|undefined)}))=} options
+     * 与 read_sheet 相同，另外支持 chunkSize（每批读取的行数，默认为1000）和
+     * chunks（为true时每次迭代返回一批行组成的数组，默认为false）。
+     * 提前 break 退出循环时会自动停止读取。
+     * @return {AsyncGenerator<Object>} 异步迭代器，依次返回行对象。
+     */
+    stream_xls: async function* (filePath, options) {
+    },
     /**
      * 复制文件，将源文件复制到指定的目标路径。
      * 
@@ -104,6 +501,7 @@ const fs = {
     },
     /**
      * 创建硬链接，将源文件链接到目标路径。
+     * 通过链接写入会修改源文件，因此源文件和目标路径都需要写权限。
      * 
      * @param {string} src 源文件的完整路径，包含文件名和扩展名。
      * @param {string} dst 目标硬链接的完整路径，包含链接名。
@@ -115,9 +513,10 @@ const fs = {
      * 读取文件内容并以字符串形式返回。
      * 
      * @param {string=} filePath 文件的完整路径，包含文件名和扩展名。可选参数，若未提供，则默认读取选择的文件。
+     * @param {string=} encoding 文件编码，如 "utf-8"、"gbk"、"gb18030"、"utf-16le"。可选参数，未提供或为 "auto" 时根据BOM和内容自动识别。
      * @return {string} 返回文件的全部内容。
      */
-    read_to_string: function (filePath) {
+    read_to_string: function (filePath, encoding) {
     },
     /**
      * 删除单个目录，该目录必须为空，否则会抛出错误。
@@ -160,18 +559,241 @@ const fs = {
      * 
      * @param {string} path 文件的完整路径，包含文件名和扩展名。
      * @param {string} contents 要写入文件的内容。
+     * @param {string=} encoding 文件编码，如 "utf-8"、"utf-8-bom"、"gbk"、"utf-16le"。可选参数，默认为 "utf-8"。
      * @return {Promise<void>} 一个Promise对象，代表异步操作的完成。
      */
-    write: function (path, contents) {
+    write: function (path, contents, encoding) {
         return Deno.core.opAsync('op_fs_write', [path, contents]);
     },
     /**
      * 逐行读取文件内容并返回字符串数组。
      * 
      * @param {string=} filePath 文件的完整路径，包含文件名和扩展名。可选参数，若未提供，则默认读取选择的文件。
+     * @param {string=} encoding 文件编码，如 "utf-8"、"gbk"、"gb18030"、"utf-16le"。可选参数，未提供或为 "auto" 时根据BOM和内容自动识别。
      * @return {Array<string>} 返回一个字符串数组，每个元素代表文件的一行内容。
      */
-    read_to_line: function (filePath) {
+    read_to_line: function (filePath, encoding) {
+    },
+    /**
+     * 读取文件的全部字节，适用于图片、压缩包等二进制文件。
+     *
+     * @param {string} filePath 文件的完整路径，包含文件名和扩展名。
+     * @return {Uint8Array} 返回文件的字节。
+     */
+    read_bytes: function (filePath) {
+    },
+    /**
+     * 读取文件中从 offset 开始的最多 length 个字节，如读取文件头。超出文件末尾的部分不返回。
+     *
+     * 例：fs.read_range("C:/data/a.zip", 0, 4)
+     *
+     * @param {string} filePath 文件的完整路径，包含文件名和扩展名。
+     * @param {number} offset 开始读取的字节位置，从0开始。
+     * @param {number} length 最多读取的字节数。
+     * @return {Uint8Array} 返回读取到的字节。
+     */
+    read_range: function (filePath, offset, length) {
+    },
+    /**
+     * 将字节写入文件，会覆盖原有内容。若文件不存在，则会创建该文件。
+     *
+     * @param {string} filePath 文件的完整路径，包含文件名和扩展名。
+     * @param {Uint8Array|ArrayBuffer|Array<number>} data 要写入的字节。
+     * @return {void} 该方法没有返回值。
+     */
+    write_bytes: function (filePath, data) {
+    },
+    /**
+     * 向指定文件末尾追加字节。若文件不存在，则会创建该文件。
+     *
+     * @param {string} filePath 文件的完整路径，包含文件名和扩展名。
+     * @param {Uint8Array|ArrayBuffer|Array<number>} data 要追加的字节。
+     * @return {void} 该方法没有返回值。
+     */
+    append_bytes: function (filePath, data) {
+    },
+    /**
+     * 递归遍历目录，返回目录下的文件和子目录，按名称排序，无权限读取的文件会被跳过。
+     *
+     * 例：fs.walk("C:/share", {include: "*.xlsx", exclude: ["backup", "**&#47;~$*"], maxDepth: 3})
+     *
+     * @param {string} root 要遍历的目录的完整路径。
+     * @param {WalkOptions=} options 遍历选项。
+     * @return {Array<FileEntry>} 返回文件和目录的数组。
+     */
+    walk: function (root, options) {
+    },
+    /**
+     * 查找与通配符匹配的文件和目录，相对路径从程序的工作目录开始。
+     *
+     * 例：fs.glob("C:/share/2024/**&#47;*.xlsx")、fs.glob("D:/export/*&#47;report.csv", {kind: "file"})
+     *
+     * @param {string} pattern 通配符路径。
+     * @param {WalkOptions=} options 遍历选项，include 由 pattern 决定。
+     * @return {Array<FileEntry>} 返回匹配的文件和目录的数组。
+     */
+    glob: function (pattern, options) {
+    },
+    /**
+     * 获取文件或目录的信息，包括大小、创建/修改/访问时间、是否只读和是否是符号链接。路径不存在时抛出错误。
+     *
+     * 例：const newest = files.map(fs.stat).sort((a, b) => b.modified - a.modified)[0]
+     *
+     * @param {string} path 文件或目录的完整路径。
+     * @return {FileStat} 返回文件信息。
+     */
+    stat: function (path) {
+    },
+    /**
+     * 异步版本的文件操作，方法名和参数与 fs 相同，返回Promise，可以配合 Promise.all 同时读写多个文件。
+     *
+     * 例：const texts = await Promise.all(files.map((f) => fs.promises.read_to_string(f)))
+     *
+     * @type {FsPromises}
+     */
+    promises: {}
+}
+
+/**
+ * 公式单元格，由 xlsx.formula 创建。
+ *
+ * @typedef {Object} FormulaCell
+ * @property {string} formula 公式文本，如 "=SUM(B2:B10)"，开头的 = 可省略。
+ * @property {(string|number|boolean)=} value 公式的缓存结果，供不会重新计算公式的查看器显示。
+ */
+
+/**
+ * 可写入单元格的值：数字、字符串、布尔值、Date（写为日期并设置日期格式）、
+ * 公式单元格，null 或 undefined 表示空单元格。
+ *
+ * @typedef {(number|string|boolean|Date|FormulaCell|null|undefined)} CellValue
+ */
+
+/**
+ * 单元格样式，未设置的属性使用Excel默认值。
+ *
+ * @typedef {Object} CellStyle
+ * @property {boolean=} bold 粗体。
+ * @property {boolean=} italic 斜体。
+ * @property {boolean=} underline 下划线。
+ * @property {string=} fontName 字体名称，如 "微软雅黑"。
+ * @property {number=} fontSize 字号，Excel默认为11。
+ * @property {string=} fontColor 字体颜色，格式为 "#RRGGBB"。
+ * @property {string=} numFormat 数字格式，如 "#,##0.00"、"0%"、"yyyy-mm-dd"。日期单元格未设置时使用 "yyyy-mm-dd" 或 "yyyy-mm-dd hh:mm:ss"。
+ * @property {string=} fill 背景填充颜色，格式为 "#RRGGBB"。
+ * @property {string=} border 四周边框："thin"、"medium"、"thick"、"dashed"、"dotted"、"double" 或 "hair"。
+ * @property {string=} borderColor 边框颜色，格式为 "#RRGGBB"。
+ * @property {string=} align 水平对齐："left"、"center" 或 "right"。
+ * @property {string=} valign 垂直对齐："top"、"center" 或 "bottom"。
+ * @property {boolean=} wrap 自动换行。
+ */
+
+/**
+ * 列设置。
+ *
+ * @typedef {Object} ColumnOptions
+ * @property {number=} width 列宽（字符数），Excel默认为8.43。
+ * @property {CellStyle=} style 列中未单独设置样式的单元格使用的样式。
+ * @property {boolean=} hidden 隐藏列。
+ */
+
+/**
+ * 行设置。
+ *
+ * @typedef {Object} RowOptions
+ * @property {number=} height 行高（磅），Excel默认为15。
+ * @property {CellStyle=} style 行中未单独设置样式的单元格使用的样式。
+ * @property {boolean=} hidden 隐藏行。
+ */
+
+/**
+ * write_rows 的选项。
+ *
+ * @typedef {Object} WriteRowsOptions
+ * @property {number=} row 起始行号，从0开始，默认为已写入的最后一行之后。
+ * @property {number=} col 起始列号，从0开始，默认为0。
+ * @property {(boolean|Array<string>)=} header 仅对对象数组有效。默认以第一个对象的键作为表头写入第一行，
+ * 为 false 时不写表头，为数组时按数组指定的键和顺序写入。
+ * @property {CellStyle=} style 数据单元格的样式。
+ * @property {CellStyle=} headerStyle 表头单元格的样式，如 {bold: true, fill: "#D9E1F2", border: "thin"}。
+ * @property {boolean=} autofilter 为true时为表头和数据区域添加自动筛选。
+ */
+
+/**
+ * 写入xlsx的工作表，由 XlsxWorkbook.add_sheet 返回，方法均返回工作表本身以便链式调用。
+ * 行号和列号均从0开始。
+ *
+ * @typedef {Object} XlsxSheet
+ * @property {number} index 工作表序号，从0开始。
+ * @property {string} name 工作表名称。
+ * @property {function(number, number, CellValue, CellStyle=): XlsxSheet} write 写入单个单元格，参数为行号、列号、值和可选的样式。
+ * @property {function(number, Array<CellValue>, number=, CellStyle=): XlsxSheet} write_row 从指定行写入一行，参数为行号、值数组、起始列号（默认为0）和可选的样式。
+ * @property {function(Array<CellValue>, CellStyle=): XlsxSheet} append_row 在已写入的最后一行之后追加一行。
+ * @property {function((Array<Array<CellValue>>|Array<Object>), WriteRowsOptions=): XlsxSheet} write_rows 写入多行，rows 可以是二维数组或对象数组。
+ * @property {function((number|string), ColumnOptions): XlsxSheet} set_column 设置列宽、列样式，列可以是列号、列字母（如 "C"）或列范围（如 "A:C"）。
+ * @property {function(number, RowOptions): XlsxSheet} set_row 设置行高、行样式。
+ * @property {function(number, number=): XlsxSheet} freeze_panes 冻结窗格，参数为冻结的行数和列数，如 freeze_panes(1) 冻结第一行。
+ * @property {function(string): XlsxSheet} autofilter 为单元格区域添加自动筛选，如 autofilter("A1:F100")。
+ */
+
+/**
+ * 新建的xlsx工作簿，由 xlsx.workbook 返回。
+ *
+ * @typedef {Object} XlsxWorkbook
+ * @property {Array<XlsxSheet>} sheets 已添加的工作表。
+ * @property {function(string=): XlsxSheet} add_sheet 添加工作表，名称省略时使用 Sheet1、Sheet2...，名称无效或已被使用时抛出错误且不添加工作表。
+ * @property {function(string): void} save 保存为xlsx文件，若文件已存在则覆盖。没有工作表时会添加一个空工作表。
+ * @property {function(): void} close 释放工作簿占用的内存，保存后不再使用时调用。
+ */
+
+/**
+ * xlsx 写入api，生成真正的xlsx文件，单元格保留数字、日期、布尔值和公式类型。
+ *
+ * 例：
+ * const wb = xlsx.workbook();
+ * wb.add_sheet("Orders")
+ *   .write_rows(rows, {headerStyle: {bold: true, fill: "#D9E1F2"}, autofilter: true})
+ *   .append_row(["合计", xlsx.formula("SUM(B2:B10)")], {bold: true, numFormat: "#,##0.00"})
+ *   .set_column("A:F", {width: 16})
+ *   .freeze_panes(1);
+ * wb.save("C:/out/orders.xlsx");
+ */
+const xlsx = {
+    /**
+     * 新建一个空的xlsx工作簿。
+     *
+     * @return {XlsxWorkbook} 返回工作簿对象。
+     */
+    workbook: function () {
+    },
+    /**
+     * 创建公式单元格。
+     *
+     * @param {string} formula 公式文本，如 "SUM(B2:B10)" 或 "=SUM(B2:B10)"。
+     * @param {(string|number|boolean)=} value 可选的缓存结果。
+     * @return {FormulaCell} 返回公式单元格。
+     */
+    formula: function (formula, value) {
+    },
+    /**
+     * 填充已有的xlsx模板并另存为新文件，模板的样式、公式、图表和其他工作表保持不变，
+     * 打开新文件时Excel会重新计算公式。
+     *
+     * values 的键可以是：
+     * 1. 定义的名称（公式 > 名称管理器），值写入名称引用区域的左上角单元格；
+     *    值为二维数组时从左上角开始逐行写入，为一维数组时单列区域向下写入，否则向右写入。
+     * 2. 单元格文本中的占位符，如 {{total}}、{{order.id}}（按路径读取嵌套对象）。
+     *    单元格只包含一个占位符时写入值本身并保留数字、日期等类型，否则替换为文本。
+     *    页眉页脚、文本框和图表标题中的占位符不会被替换。
+     *
+     * 例：xlsx.fill_template("", "C:/out/report.xlsx", {Total: 1280.5, date: new Date(), items: [[1, "A"], [2, "B"]]})
+     *
+     * @param {string=} src 模板文件路径，传入空字符串或undefined时使用选择的文件。仅支持 xlsx 格式。
+     * @param {string} dst 保存的文件路径，不能与模板相同，若文件已存在则覆盖。
+     * @param {Object} values 要填入的数据。
+     * @return {{cells: number, missing: Array<string>}} 返回写入的单元格数量和模板中没有对应值的占位符。
+     */
+    fill_template: function (src, dst, values) {
     }
 }
 
@@ -206,6 +828,28 @@ function md5(content) {
 }
 
 
+/**
+ * 将从0开始的列序号转换为列字母，支持 A 到 XFD（Excel 的全部 16384 列）。
+ * 例：column_name(0) 返回 "A"，column_name(27) 返回 "AB"。
+ *
+ * @param {number} index 列序号，从0开始，最大为16383。
+ * @return {string} 返回列字母。
+ */
+function column_name(index) {
+    return "";
+}
+
+/**
+ * 将列字母转换为从0开始的列序号，不区分大小写，支持 A 到 XFD。
+ * 例：column_index("A") 返回 0，column_index("AB") 返回 27。
+ *
+ * @param {string} name 列字母。
+ * @return {number} 返回列序号。
+ */
+function column_index(name) {
+    return 0;
+}
+
 /**
  * 打印指定的文本内容，具体的输出方式取决于运行环境。
  * 
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE file DROP COLUMN max_heap_mb;
+ALTER TABLE file DROP COLUMN timeout_secs;
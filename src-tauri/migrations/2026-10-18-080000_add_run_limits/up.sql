@@ -0,0 +1,3 @@
+-- 脚本运行的超时时间（秒）和V8堆内存上限（MB），为空时使用默认值
+ALTER TABLE file ADD COLUMN timeout_secs INTEGER;
+ALTER TABLE file ADD COLUMN max_heap_mb INTEGER;
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE file DROP COLUMN prompt_access;
+ALTER TABLE file DROP COLUMN write_dirs;
+ALTER TABLE file DROP COLUMN read_dirs;
+ALTER TABLE file DROP COLUMN output_dir;
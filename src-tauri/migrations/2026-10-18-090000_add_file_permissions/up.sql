@@ -0,0 +1,5 @@
+-- 脚本的输出目录、允许读取和写入的目录（一行一个），以及访问其他目录时是否弹窗询问
+ALTER TABLE file ADD COLUMN output_dir TEXT;
+ALTER TABLE file ADD COLUMN read_dirs TEXT;
+ALTER TABLE file ADD COLUMN write_dirs TEXT;
+ALTER TABLE file ADD COLUMN prompt_access BOOLEAN;
@@ -5,7 +5,9 @@ use crate::dao::schema::file::{code, created_date, id};
 use diesel::associations::HasTable;
 use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, SelectableHelper};
 
-use super::schema::file::{name, xlx_template};
+use super::schema::file::{
+    max_heap_mb, name, output_dir, prompt_access, read_dirs, timeout_secs, write_dirs, xlx_template,
+};
 
 pub(crate) fn select() -> anyhow::Result<Vec<XlsFile>> {
     let mut connection = db::establish_db_connection();
@@ -51,10 +53,39 @@ pub(crate) fn update_name_xls_by_id(
     id_where: i32,
     name_set: String,
     xls_set: String,
+    timeout_set: Option<i32>,
+    max_heap_set: Option<i32>,
 ) -> anyhow::Result<XlsFile> {
     let mut connection = db::establish_db_connection();
     let _ = diesel::update(file)
-        .set((name.eq(&name_set), xlx_template.eq(&xls_set)))
+        .set((
+            name.eq(&name_set),
+            xlx_template.eq(&xls_set),
+            timeout_secs.eq(timeout_set),
+            max_heap_mb.eq(max_heap_set),
+        ))
+        .filter(id.eq(&id_where))
+        .execute(&mut connection)?;
+    Ok(file
+        .filter(id.eq(id_where))
+        .first::<XlsFile>(&mut connection)?)
+}
+
+pub(crate) fn update_permissions_by_id(
+    id_where: i32,
+    output_set: Option<String>,
+    read_set: Option<String>,
+    write_set: Option<String>,
+    prompt_set: Option<bool>,
+) -> anyhow::Result<XlsFile> {
+    let mut connection = db::establish_db_connection();
+    let _ = diesel::update(file)
+        .set((
+            output_dir.eq(output_set),
+            read_dirs.eq(read_set),
+            write_dirs.eq(write_set),
+            prompt_access.eq(prompt_set),
+        ))
         .filter(id.eq(&id_where))
         .execute(&mut connection)?;
     Ok(file
@@ -94,6 +125,12 @@ mod tests {
             code: "test".to_string(),
             created_date: Some(Local::now().naive_local()),
             updated_date: Some(Local::now().naive_local()),
+            timeout_secs: None,
+            max_heap_mb: None,
+            output_dir: None,
+            read_dirs: None,
+            write_dirs: None,
+            prompt_access: None,
         })
         .unwrap();
         assert_eq!(res.name, "test");
@@ -108,6 +145,12 @@ mod tests {
             code: "test".to_string(),
             created_date: Some(Local::now().naive_local()),
             updated_date: Some(Local::now().naive_local()),
+            timeout_secs: Some(60),
+            max_heap_mb: None,
+            output_dir: None,
+            read_dirs: Some("/data".to_string()),
+            write_dirs: None,
+            prompt_access: Some(false),
         };
         let res = update(file_add.clone()).unwrap();
         assert_eq!(res, file_add)
@@ -15,6 +15,18 @@ pub struct XlsFile {
     pub code: String,
     pub created_date: Option<NaiveDateTime>,
     pub updated_date: Option<NaiveDateTime>,
+    // 运行超时时间（秒），为空时不限制
+    pub timeout_secs: Option<i32>,
+    // V8堆内存上限（MB），为空时使用默认值
+    pub max_heap_mb: Option<i32>,
+    // 输出目录，为空时使用 ~/xlsDsl/output
+    pub output_dir: Option<String>,
+    // 额外允许读取的目录，一行一个
+    pub read_dirs: Option<String>,
+    // 额外允许写入的目录，一行一个
+    pub write_dirs: Option<String>,
+    // 访问不允许的目录时弹窗询问
+    pub prompt_access: Option<bool>,
 }
 
 #[derive(Insertable, Clone, Debug, Serialize, Deserialize)]
@@ -26,6 +38,18 @@ pub struct NewFile {
     pub code: String,
     pub created_date: Option<NaiveDateTime>,
     pub updated_date: Option<NaiveDateTime>,
+    // 运行超时时间（秒），为空时不限制
+    pub timeout_secs: Option<i32>,
+    // V8堆内存上限（MB），为空时使用默认值
+    pub max_heap_mb: Option<i32>,
+    // 输出目录，为空时使用 ~/xlsDsl/output
+    pub output_dir: Option<String>,
+    // 额外允许读取的目录，一行一个
+    pub read_dirs: Option<String>,
+    // 额外允许写入的目录，一行一个
+    pub write_dirs: Option<String>,
+    // 访问不允许的目录时弹窗询问
+    pub prompt_access: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -56,4 +80,18 @@ impl RunLog {
             msg,
         }
     }
+
+    pub fn cancelled(msg: String) -> Self {
+        RunLog {
+            log_type: "cancelled".to_string(),
+            msg,
+        }
+    }
+
+    pub fn report(msg: String) -> Self {
+        RunLog {
+            log_type: "report".to_string(),
+            msg,
+        }
+    }
 }
@@ -6,5 +6,11 @@ diesel::table! {
         code -> Text,
         created_date -> Nullable<Timestamp>,
         updated_date -> Nullable<Timestamp>,
+        timeout_secs -> Nullable<Integer>,
+        max_heap_mb -> Nullable<Integer>,
+        output_dir -> Nullable<Text>,
+        read_dirs -> Nullable<Text>,
+        write_dirs -> Nullable<Text>,
+        prompt_access -> Nullable<Bool>,
     }
 }
@@ -0,0 +1,139 @@
+// 试运行：脚本对文件系统的修改只记录为计划的变更，不会写入磁盘，运行结束后返回变更报告
+use std::cell::RefCell;
+
+use serde::Serialize;
+
+use crate::{dao::models::RunLog, deno::lib::emit_log};
+
+thread_local! {
+    // 当前运行计划的变更，为空时正常运行
+    static DRY_RUN: RefCell<Option<Vec<PlannedChange>>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum Operation {
+    Write,
+    Append,
+    CreateFile,
+    CreateDir,
+    Remove,
+    RemoveDir,
+    Rename,
+    Copy,
+    HardLink,
+}
+
+impl Operation {
+    fn name(&self) -> &'static str {
+        match self {
+            Operation::Write => "write",
+            Operation::Append => "append",
+            Operation::CreateFile => "create file",
+            Operation::CreateDir => "create dir",
+            Operation::Remove => "remove",
+            Operation::RemoveDir => "remove dir",
+            Operation::Rename => "rename",
+            Operation::Copy => "copy",
+            Operation::HardLink => "hard link",
+        }
+    }
+}
+
+// 一次计划的文件系统变更
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PlannedChange {
+    pub operation: Operation,
+    pub path: String,
+    // 重命名、复制和硬链接的目标路径
+    pub target: Option<String>,
+    // 写入的字节数，创建目录、删除和重命名时为空
+    pub bytes: Option<u64>,
+}
+
+// 试运行结束后的报告
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DryRunReport {
+    pub changes: Vec<PlannedChange>,
+    // 计划写入的总字节数
+    pub bytes: u64,
+}
+
+// 开始试运行，之后的文件修改只记录不执行
+pub(crate) fn start_dry_run() {
+    DRY_RUN.with(|d| *d.borrow_mut() = Some(Vec::new()));
+}
+
+// 结束试运行并返回报告，不在试运行中时返回 None
+pub(crate) fn finish_dry_run() -> Option<DryRunReport> {
+    let changes = DRY_RUN.with(|d| d.borrow_mut().take())?;
+    let bytes = changes.iter().filter_map(|c| c.bytes).sum();
+    Some(DryRunReport { changes, bytes })
+}
+
+// 当前是否在试运行
+pub(crate) fn is_dry_run() -> bool {
+    DRY_RUN.with(|d| d.borrow().is_some())
+}
+
+// 试运行时记录计划的变更并返回 true，调用方不再修改磁盘，正常运行时返回 false
+/// # 参数
+/// - `operation`: 变更的类型。
+/// - `path`: 变更的路径，重命名、复制和硬链接时为源路径。
+/// - `target`: 重命名、复制和硬链接的目标路径。
+/// - `bytes`: 写入的字节数。
+pub(crate) fn plan(
+    operation: Operation,
+    path: &str,
+    target: Option<&str>,
+    bytes: Option<u64>,
+) -> bool {
+    let change = PlannedChange {
+        operation,
+        path: path.to_string(),
+        target: target.map(str::to_string),
+        bytes,
+    };
+    let planned = DRY_RUN.with(|d| match d.borrow_mut().as_mut() {
+        Some(changes) => {
+            changes.push(change.clone());
+            true
+        }
+        None => false,
+    });
+    if planned {
+        let mut msg = format!("[dry run] {} {}", operation.name(), change.path);
+        if let Some(target) = &change.target {
+            msg.push_str(&format!(" -> {}", target));
+        }
+        if let Some(bytes) = change.bytes {
+            msg.push_str(&format!(" ({} bytes)", bytes));
+        }
+        emit_log("println", RunLog::log(msg));
+    }
+    planned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_planned_changes() {
+        assert!(!plan(Operation::Write, "a.txt", None, Some(3)));
+        assert!(finish_dry_run().is_none());
+
+        start_dry_run();
+        assert!(is_dry_run());
+        assert!(plan(Operation::Write, "a.txt", None, Some(3)));
+        assert!(plan(Operation::Rename, "a.txt", Some("b.txt"), None));
+        assert!(plan(Operation::Copy, "b.txt", Some("c.txt"), Some(3)));
+        let report = finish_dry_run().unwrap();
+        assert_eq!(report.changes.len(), 3);
+        assert_eq!(report.bytes, 6);
+        assert_eq!(report.changes[1].target.as_deref(), Some("b.txt"));
+        assert!(finish_dry_run().is_none());
+    }
+}
@@ -0,0 +1,381 @@
+// 异步文件操作函数，与 fs_funs 中的同步函数一一对应，返回 Promise，
+// 文件读写在 tokio 的阻塞线程池中执行，不会阻塞脚本的事件循环
+use std::path::Path;
+
+use deno_core::{error::AnyError, op2, JsBuffer};
+use tokio::{
+    fs,
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+};
+
+use super::{
+    dry_run::Operation,
+    fs_funs::{
+        prepare_append_text, prepare_copy, prepare_hard_link, prepare_rename, prepare_write,
+        prepare_write_text,
+    },
+    permissions::{check_follow_links, check_read},
+};
+use crate::{
+    encoding::lib::{decode, TextEncoding},
+    files::{
+        stat::{stat, FileStat},
+        walk::{glob, split_glob, walk, FileEntry, WalkOptions},
+    },
+};
+
+// 公共方法：创建文件所在的文件夹
+async fn create_parent_dir(path: &str) {
+    if let Some(parent) = Path::new(path).parent() {
+        let _ = fs::create_dir_all(parent).await;
+    }
+}
+
+// 公共方法：按指定编码读取文本文件，编码为空或为 auto 时自动识别
+async fn read_text(path: &str, encoding: &str) -> Result<String, AnyError> {
+    let bytes = fs::read(path).await?;
+    decode(&bytes, TextEncoding::from_label(encoding)?)
+}
+
+// 公共方法：追加字节到文件末尾，文件不存在时创建
+async fn append_bytes(path: &str, data: &[u8]) -> Result<(), AnyError> {
+    create_parent_dir(path).await;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?;
+    file.write_all(data).await?;
+    Ok(())
+}
+
+// 异步复制文件，将源文件复制到目标路径
+/// # 参数
+/// - `src`: 源文件的路径。
+/// - `dst`: 目标文件的路径。
+#[op2(async)]
+pub async fn op_fs_copy_file_async(
+    #[string] src: String,
+    #[string] dst: String,
+) -> Result<(), AnyError> {
+    if prepare_copy(&src, &dst)? {
+        return Ok(());
+    }
+    fs::copy(src, dst).await?;
+    Ok(())
+}
+
+// 异步创建单个目录，若目录已存在则报错
+/// # 参数
+/// - `path`: 要创建的目录的路径。
+#[op2(async)]
+pub async fn op_fs_create_dir_async(#[string] path: String) -> Result<(), AnyError> {
+    if prepare_write(Operation::CreateDir, &path, None)? {
+        return Ok(());
+    }
+    fs::create_dir(path).await?;
+    Ok(())
+}
+
+// 异步递归创建目录，若目录已存在则不报错
+/// # 参数
+/// - `path`: 要递归创建的目录的路径。
+#[op2(async)]
+pub async fn op_fs_create_dir_all_async(#[string] path: String) -> Result<(), AnyError> {
+    if prepare_write(Operation::CreateDir, &path, None)? {
+        return Ok(());
+    }
+    fs::create_dir_all(path).await?;
+    Ok(())
+}
+
+// 异步检查指定路径的文件或目录是否存在
+/// # 参数
+/// - `path`: 要检查的文件或目录的路径。
+#[op2(async)]
+pub async fn op_fs_exists_async(#[string] path: String) -> Result<bool, AnyError> {
+    check_read(&path)?;
+    Ok(fs::metadata(path).await.is_ok())
+}
+
+// 异步创建硬链接，将源文件链接到目标路径
+/// # 参数
+/// - `src`: 源文件的路径。
+/// - `dst`: 目标硬链接的路径。
+#[op2(async)]
+pub async fn op_fs_hard_link_async(
+    #[string] src: String,
+    #[string] dst: String,
+) -> Result<(), AnyError> {
+    if prepare_hard_link(&src, &dst)? {
+        return Ok(());
+    }
+    fs::hard_link(src, dst).await?;
+    Ok(())
+}
+
+// 异步读取文件内容并以字符串形式返回
+/// # 参数
+/// - `path`: 要读取的文件的路径。
+/// - `encoding`: 文件编码，为空或为 auto 时自动识别。
+#[op2(async)]
+#[string]
+pub async fn op_fs_read_to_string_async(
+    #[string] path: String,
+    #[string] encoding: String,
+) -> Result<String, AnyError> {
+    check_read(&path)?;
+    read_text(&path, &encoding).await
+}
+
+// 异步读取目录下的所有文件和目录
+/// # 参数
+/// - `path`: 要读取的目录的路径。
+#[op2(async)]
+#[serde]
+pub async fn op_fs_read_dir_async(#[string] path: String) -> Result<Vec<String>, AnyError> {
+    check_read(&path)?;
+    let mut dir = fs::read_dir(path).await?;
+    let mut res = Vec::new();
+    while let Some(entry) = dir.next_entry().await? {
+        res.push(entry.path().to_string_lossy().to_string());
+    }
+    Ok(res)
+}
+
+// 异步递归遍历目录，遍历在阻塞线程池中执行
+/// # 参数
+/// - `root`: 要遍历的目录的路径。
+/// - `options`: 遍历选项。
+#[op2(async)]
+#[serde]
+pub async fn op_fs_walk_async(
+    #[string] root: String,
+    #[serde] options: WalkOptions,
+) -> Result<Vec<FileEntry>, AnyError> {
+    check_read(&root)?;
+    check_follow_links(options.follow_links)?;
+    tokio::task::spawn_blocking(move || walk(&root, &options)).await?
+}
+
+// 异步查找与通配符匹配的文件和目录
+/// # 参数
+/// - `pattern`: 通配符路径。
+/// - `options`: 遍历选项。
+#[op2(async)]
+#[serde]
+pub async fn op_fs_glob_async(
+    #[string] pattern: String,
+    #[serde] options: WalkOptions,
+) -> Result<Vec<FileEntry>, AnyError> {
+    // 只检查通配符之前的目录，匹配的文件都在该目录下
+    check_read(&split_glob(&pattern).0.to_string_lossy())?;
+    check_follow_links(options.follow_links)?;
+    let cwd = std::env::current_dir()?;
+    tokio::task::spawn_blocking(move || glob(&pattern, &cwd.to_string_lossy(), &options)).await?
+}
+
+// 异步获取文件或目录的信息
+/// # 参数
+/// - `path`: 文件或目录的路径。
+#[op2(async)]
+#[serde]
+pub async fn op_fs_stat_async(#[string] path: String) -> Result<FileStat, AnyError> {
+    check_read(&path)?;
+    tokio::task::spawn_blocking(move || stat(&path)).await?
+}
+
+// 异步判断是否是目录
+/// # 参数
+/// - `path`: 要判断的路径。
+#[op2(async)]
+pub async fn op_fs_is_dir_async(#[string] path: String) -> Result<bool, AnyError> {
+    check_read(&path)?;
+    Ok(fs::metadata(path).await?.is_dir())
+}
+
+// 异步判断是否是文件
+/// # 参数
+/// - `path`: 要判断的路径。
+#[op2(async)]
+pub async fn op_fs_is_file_async(#[string] path: String) -> Result<bool, AnyError> {
+    check_read(&path)?;
+    Ok(fs::metadata(path).await?.is_file())
+}
+
+// 异步删除单个目录，目录必须为空
+/// # 参数
+/// - `path`: 要删除的空目录的路径。
+#[op2(async)]
+pub async fn op_fs_remove_dir_async(#[string] path: String) -> Result<(), AnyError> {
+    if prepare_write(Operation::RemoveDir, &path, None)? {
+        return Ok(());
+    }
+    fs::remove_dir(path).await?;
+    Ok(())
+}
+
+// 异步递归删除目录及其所有内容
+/// # 参数
+/// - `path`: 要递归删除的目录的路径。
+#[op2(async)]
+pub async fn op_fs_remove_dir_all_async(#[string] path: String) -> Result<(), AnyError> {
+    if prepare_write(Operation::RemoveDir, &path, None)? {
+        return Ok(());
+    }
+    fs::remove_dir_all(path).await?;
+    Ok(())
+}
+
+// 异步删除指定文件
+/// # 参数
+/// - `path`: 要删除的文件的路径。
+#[op2(async)]
+pub async fn op_fs_remove_file_async(#[string] path: String) -> Result<(), AnyError> {
+    if prepare_write(Operation::Remove, &path, None)? {
+        return Ok(());
+    }
+    fs::remove_file(path).await?;
+    Ok(())
+}
+
+// 异步重命名文件或移动文件到新路径
+/// # 参数
+/// - `src`: 源文件的路径。
+/// - `dst`: 目标文件的路径。
+#[op2(async)]
+pub async fn op_fs_rename_async(
+    #[string] src: String,
+    #[string] dst: String,
+) -> Result<(), AnyError> {
+    if prepare_rename(&src, &dst)? {
+        return Ok(());
+    }
+    fs::rename(src, dst).await?;
+    Ok(())
+}
+
+// 异步将指定内容写入文件，会覆盖原有内容
+/// # 参数
+/// - `path`: 要写入内容的文件的路径。
+/// - `contents`: 要写入文件的内容。
+/// - `encoding`: 文件编码，为空时使用utf-8。
+#[op2(async)]
+pub async fn op_fs_write_async(
+    #[string] path: String,
+    #[string] contents: String,
+    #[string] encoding: String,
+) -> Result<(), AnyError> {
+    let Some(bytes) = prepare_write_text(&path, &contents, &encoding)? else {
+        return Ok(());
+    };
+    create_parent_dir(&path).await;
+    fs::write(path, bytes).await?;
+    Ok(())
+}
+
+// 异步逐行读取文件内容并返回字符串向量
+/// # 参数
+/// - `path`: 要逐行读取的文件的路径。
+/// - `encoding`: 文件编码，为空或为 auto 时自动识别。
+#[op2(async)]
+#[serde]
+pub async fn op_fs_read_line_async(
+    #[string] path: String,
+    #[string] encoding: String,
+) -> Result<Vec<String>, AnyError> {
+    check_read(&path)?;
+    let text = read_text(&path, &encoding).await?;
+    Ok(text.lines().map(|line| line.to_string()).collect())
+}
+
+// 异步追加内容到文件末尾
+/// # 参数
+/// - `file`: 要追加内容的文件的路径。
+/// - `contents`: 要追加到文件末尾的内容。
+/// - `encoding`: 文件编码，需与文件原有内容一致，为空时使用utf-8。
+#[op2(async)]
+pub async fn op_fs_append_async(
+    #[string] file: String,
+    #[string] contents: String,
+    #[string] encoding: String,
+) -> Result<(), AnyError> {
+    let Some(bytes) = prepare_append_text(&file, &contents, &encoding)? else {
+        return Ok(());
+    };
+    append_bytes(&file, &bytes).await
+}
+
+// 异步创建新文件，若文件已存在则会截断文件
+/// # 参数
+/// - `path`: 要创建或截断的文件的路径。
+#[op2(async)]
+pub async fn op_fs_create_file_async(#[string] path: String) -> Result<(), AnyError> {
+    if prepare_write(Operation::CreateFile, &path, Some(0))? {
+        return Ok(());
+    }
+    fs::File::create(path).await?;
+    Ok(())
+}
+
+// 异步读取文件的全部字节
+/// # 参数
+/// - `path`: 要读取的文件的路径。
+#[op2(async)]
+#[buffer]
+pub async fn op_fs_read_bytes_async(#[string] path: String) -> Result<Vec<u8>, AnyError> {
+    check_read(&path)?;
+    Ok(fs::read(path).await?)
+}
+
+// 异步读取文件从指定位置开始的字节，超出文件末尾的部分不返回
+/// # 参数
+/// - `path`: 要读取的文件的路径。
+/// - `offset`: 开始读取的字节位置，从0开始。
+/// - `length`: 最多读取的字节数。
+#[op2(async)]
+#[buffer]
+pub async fn op_fs_read_range_async(
+    #[string] path: String,
+    #[number] offset: u64,
+    #[number] length: u64,
+) -> Result<Vec<u8>, AnyError> {
+    check_read(&path)?;
+    let mut file = fs::File::open(path).await?;
+    file.seek(std::io::SeekFrom::Start(offset)).await?;
+    let mut buf = Vec::new();
+    file.take(length).read_to_end(&mut buf).await?;
+    Ok(buf)
+}
+
+// 异步将字节写入文件，会覆盖原有内容
+/// # 参数
+/// - `path`: 要写入的文件的路径。
+/// - `data`: 要写入的字节。
+#[op2(async)]
+pub async fn op_fs_write_bytes_async(
+    #[string] path: String,
+    #[buffer] data: JsBuffer,
+) -> Result<(), AnyError> {
+    if prepare_write(Operation::Write, &path, Some(data.len() as u64))? {
+        return Ok(());
+    }
+    create_parent_dir(&path).await;
+    fs::write(path, &*data).await?;
+    Ok(())
+}
+
+// 异步追加字节到文件末尾
+/// # 参数
+/// - `path`: 要追加内容的文件的路径。
+/// - `data`: 要追加的字节。
+#[op2(async)]
+pub async fn op_fs_append_bytes_async(
+    #[string] path: String,
+    #[buffer] data: JsBuffer,
+) -> Result<(), AnyError> {
+    if prepare_write(Operation::Append, &path, Some(data.len() as u64))? {
+        return Ok(());
+    }
+    append_bytes(&path, &data).await
+}
@@ -1,17 +1,32 @@
 // 文件操作函数
 use std::{
     fs,
-    io::{BufRead, Write},
+    io::{Read, Seek, SeekFrom, Write},
 };
 
 use deno_core::{error::AnyError, op2};
 
+use super::{
+    dry_run::{plan, Operation},
+    permissions::{check_follow_links, check_hard_link, check_read, check_write},
+};
+use crate::{
+    encoding::lib::{decode, encode, TextEncoding},
+    files::{
+        stat::{stat, FileStat},
+        walk::{glob, split_glob, walk, FileEntry, WalkOptions},
+    },
+};
+
 // 复制文件，将源文件复制到目标路径
 /// # 参数
 /// - `src`: 源文件的路径，以字符串形式表示。
 /// - `dst`: 目标文件的路径，以字符串形式表示。
 #[op2(fast)]
 pub fn op_fs_copy_file(#[string] src: String, #[string] dst: String) -> Result<(), AnyError> {
+    if prepare_copy(&src, &dst)? {
+        return Ok(());
+    }
     fs::copy(src, dst)?;
     Ok(())
 }
@@ -21,6 +36,9 @@ pub fn op_fs_copy_file(#[string] src: String, #[string] dst: String) -> Result<(
 /// - `path`: 要创建的目录的路径，以字符串形式表示。
 #[op2(fast)]
 pub fn op_fs_create_dir(#[string] path: String) -> Result<(), AnyError> {
+    if prepare_write(Operation::CreateDir, &path, None)? {
+        return Ok(());
+    }
     fs::create_dir(path)?;
     Ok(())
 }
@@ -30,6 +48,9 @@ pub fn op_fs_create_dir(#[string] path: String) -> Result<(), AnyError> {
 /// - `path`: 要递归创建的目录的路径，以字符串形式表示。
 #[op2(fast)]
 pub fn op_fs_create_dir_all(#[string] path: String) -> Result<(), AnyError> {
+    if prepare_write(Operation::CreateDir, &path, None)? {
+        return Ok(());
+    }
     fs::create_dir_all(path)?;
     Ok(())
 }
@@ -39,6 +60,7 @@ pub fn op_fs_create_dir_all(#[string] path: String) -> Result<(), AnyError> {
 /// - `path`: 要检查的文件或目录的路径，以字符串形式表示。
 #[op2(fast)]
 pub fn op_fs_exists(#[string] path: String) -> Result<bool, AnyError> {
+    check_read(&path)?;
     Ok(fs::metadata(path).is_ok())
 }
 
@@ -48,25 +70,99 @@ pub fn op_fs_exists(#[string] path: String) -> Result<bool, AnyError> {
 /// - `dst`: 目标硬链接的路径，以字符串形式表示。
 #[op2(fast)]
 pub fn op_fs_hard_link(#[string] src: String, #[string] dst: String) -> Result<(), AnyError> {
+    if prepare_hard_link(&src, &dst)? {
+        return Ok(());
+    }
     fs::hard_link(src, dst)?;
     Ok(())
 }
 
+// 公共方法：按指定编码读取文本文件，编码为空或为 auto 时自动识别
+fn read_text(path: &str, encoding: &str) -> Result<String, AnyError> {
+    let bytes = fs::read(path)?;
+    decode(&bytes, TextEncoding::from_label(encoding)?)
+}
+
+// 公共方法：按指定编码将文本转换为字节，编码为空时使用不带BOM的utf-8
+pub(super) fn encode_text(contents: &str, encoding: &str, bom: bool) -> Result<Vec<u8>, AnyError> {
+    let mut encoding = TextEncoding::from_label(encoding)?.unwrap_or(TextEncoding::UTF8);
+    encoding.bom &= bom;
+    encode(contents, encoding)
+}
+
+// 公共方法：检查路径的写权限，试运行时只记录，返回 true 表示不需要实际执行
+pub(super) fn prepare_write(
+    operation: Operation,
+    path: &str,
+    bytes: Option<u64>,
+) -> Result<bool, AnyError> {
+    check_write(path)?;
+    Ok(plan(operation, path, None, bytes))
+}
+
+// 公共方法：检查复制文件的权限，试运行时只记录
+pub(super) fn prepare_copy(src: &str, dst: &str) -> Result<bool, AnyError> {
+    check_read(src)?;
+    check_write(dst)?;
+    let bytes = fs::metadata(src)?.len();
+    Ok(plan(Operation::Copy, src, Some(dst), Some(bytes)))
+}
+
+// 公共方法：检查创建硬链接的权限，试运行时只记录
+pub(super) fn prepare_hard_link(src: &str, dst: &str) -> Result<bool, AnyError> {
+    check_hard_link(src, dst)?;
+    Ok(plan(Operation::HardLink, src, Some(dst), None))
+}
+
+// 公共方法：检查重命名的权限，试运行时只记录
+pub(super) fn prepare_rename(src: &str, dst: &str) -> Result<bool, AnyError> {
+    check_write(src)?;
+    check_write(dst)?;
+    Ok(plan(Operation::Rename, src, Some(dst), None))
+}
+
+// 公共方法：检查写入文本的权限并转换编码，试运行时只记录并返回 None
+pub(super) fn prepare_write_text(
+    path: &str,
+    contents: &str,
+    encoding: &str,
+) -> Result<Option<Vec<u8>>, AnyError> {
+    check_write(path)?;
+    let bytes = encode_text(contents, encoding, true)?;
+    if plan(Operation::Write, path, None, Some(bytes.len() as u64)) {
+        return Ok(None);
+    }
+    Ok(Some(bytes))
+}
+
+// 公共方法：检查追加文本的权限并转换编码，试运行时只记录并返回 None
+pub(super) fn prepare_append_text(
+    path: &str,
+    contents: &str,
+    encoding: &str,
+) -> Result<Option<Vec<u8>>, AnyError> {
+    check_write(path)?;
+    // 只有空文件才需要写入BOM
+    let empty = fs::metadata(path).map(|m| m.len()).unwrap_or(0) == 0;
+    let bytes = encode_text(contents, encoding, empty)?;
+    if plan(Operation::Append, path, None, Some(bytes.len() as u64)) {
+        return Ok(None);
+    }
+    Ok(Some(bytes))
+}
+
 // 读取文件内容并以字符串形式返回
 /// # 参数
 /// - `path`: 要读取的文件的路径，以字符串形式表示。
+/// - `encoding`: 文件编码，如 utf-8、gbk、gb18030、utf-16le，为空或为 auto 时自动识别。
 #[op2]
 #[string]
-pub fn op_fs_read_to_string(#[string] path: String) -> Result<String, AnyError> {
-    let contents = fs::read_to_string(path);
-    match contents {
-        Ok(c) => {
-            return Ok(c);
-        }
-        Err(e) => {
-            return Err(AnyError::from(e));
-        }
-    }
+pub fn op_fs_read_to_string(
+    #[string] path: String,
+    #[string] encoding: String,
+) -> Result<String, AnyError> {
+    check_read(&path)?;
+    read_text(&path, &encoding)
 }
 
 // 读取所有目录
@@ -75,6 +171,7 @@ pub fn op_fs_read_to_string(#[string] path: String) -> Result<String, AnyError>
 #[op2]
 #[serde]
 pub fn op_fs_read_dir(#[string] path: String) -> Result<Vec<String>, AnyError> {
+    check_read(&path)?;
     let dir = fs::read_dir(path);
     match dir {
         Ok(d) => {
@@ -92,11 +189,22 @@ pub fn op_fs_read_dir(#[string] path: String) -> Result<Vec<String>, AnyError> {
     }
 }
 
+// 获取文件或目录的信息，包括大小、创建/修改/访问时间、是否只读和是否是符号链接
+/// # 参数
+/// - `path`: 文件或目录的路径，以字符串形式表示。
+#[op2]
+#[serde]
+pub fn op_fs_stat(#[string] path: String) -> Result<FileStat, AnyError> {
+    check_read(&path)?;
+    stat(&path)
+}
+
 // 判断是否是目录
 /// # 参数
 /// - `path`: 要判断的路径，以字符串形式表示。
 #[op2(fast)]
 pub fn op_fs_is_dir(#[string] path: String) -> Result<bool, AnyError> {
+    check_read(&path)?;
     Ok(fs::metadata(path)?.is_dir())
 }
 
@@ -105,6 +213,7 @@ pub fn op_fs_is_dir(#[string] path: String) -> Result<bool, AnyError> {
 /// - `path`: 要判断的路径，以字符串形式表示。
 #[op2(fast)]
 pub fn op_fs_is_file(#[string] path: String) -> Result<bool, AnyError> {
+    check_read(&path)?;
     Ok(fs::metadata(path)?.is_file())
 }
 
@@ -113,6 +222,9 @@ pub fn op_fs_is_file(#[string] path: String) -> Result<bool, AnyError> {
 /// - `path`: 要删除的空目录的路径，以字符串形式表示。
 #[op2(fast)]
 pub fn op_fs_remove_dir(#[string] path: String) -> Result<(), AnyError> {
+    if prepare_write(Operation::RemoveDir, &path, None)? {
+        return Ok(());
+    }
     fs::remove_dir(path)?;
     Ok(())
 }
@@ -122,6 +234,9 @@ pub fn op_fs_remove_dir(#[string] path: String) -> Result<(), AnyError> {
 /// - `path`: 要递归删除的目录的路径，以字符串形式表示。
 #[op2(fast)]
 pub fn op_fs_remove_dir_all(#[string] path: String) -> Result<(), AnyError> {
+    if prepare_write(Operation::RemoveDir, &path, None)? {
+        return Ok(());
+    }
     fs::remove_dir_all(path)?;
     Ok(())
 }
@@ -131,6 +246,9 @@ pub fn op_fs_remove_dir_all(#[string] path: String) -> Result<(), AnyError> {
 /// - `path`: 要删除的文件的路径，以字符串形式表示。
 #[op2(fast)]
 pub fn op_fs_remove_file(#[string] path: String) -> Result<(), AnyError> {
+    if prepare_write(Operation::Remove, &path, None)? {
+        return Ok(());
+    }
     fs::remove_file(path)?;
     Ok(())
 }
@@ -141,6 +259,9 @@ pub fn op_fs_remove_file(#[string] path: String) -> Result<(), AnyError> {
 /// - `dst`: 目标文件的路径，以字符串形式表示。
 #[op2(fast)]
 pub fn op_fs_rename(#[string] src: String, #[string] dst: String) -> Result<(), AnyError> {
+    if prepare_rename(&src, &dst)? {
+        return Ok(());
+    }
     fs::rename(src, dst)?;
     Ok(())
 }
@@ -166,47 +287,55 @@ fn create_file_if_not_exists(path: &str) -> Result<(), AnyError> {
 /// # 参数
 /// - `path`: 要写入内容的文件的路径，以字符串形式表示。
 /// - `contents`: 要写入文件的内容，以字符串形式表示。
+/// - `encoding`: 文件编码，如 utf-8、utf-8-bom、gbk、utf-16le，为空时使用utf-8。
 #[op2(fast)]
-pub fn op_fs_write(#[string] path: String, #[string] contents: String) -> Result<(), AnyError> {
+pub fn op_fs_write(
+    #[string] path: String,
+    #[string] contents: String,
+    #[string] encoding: String,
+) -> Result<(), AnyError> {
+    let Some(bytes) = prepare_write_text(&path, &contents, &encoding)? else {
+        return Ok(());
+    };
     create_file_if_not_exists(&path)?;
-    fs::write(path, contents)?;
+    fs::write(path, bytes)?;
     Ok(())
 }
 
 // 逐行读取文件内容并返回字符串向量
 /// # 参数
 /// - `path`: 要逐行读取的文件的路径，以字符串形式表示。
+/// - `encoding`: 文件编码，如 utf-8、gbk、gb18030、utf-16le，为空或为 auto 时自动识别。
 #[op2]
 #[serde]
-pub fn op_fs_read_line(#[string] path: String) -> Result<Vec<String>, AnyError> {
-    let file = std::fs::File::open(path);
-    match file {
-        Ok(f) => {
-            let reader = std::io::BufReader::new(f);
-            let mut lines_res = Vec::new();
-            for line in reader.lines() {
-                let line = line.unwrap();
-                lines_res.push(line);
-            }
-            return Ok(lines_res);
-        }
-        Err(e) => {
-            return Err(AnyError::from(e));
-        }
-    }
+pub fn op_fs_read_line(
+    #[string] path: String,
+    #[string] encoding: String,
+) -> Result<Vec<String>, AnyError> {
+    check_read(&path)?;
+    let text = read_text(&path, &encoding)?;
+    Ok(text.lines().map(|line| line.to_string()).collect())
 }
 
 // 追加内容到文件末尾
 /// # 参数
 /// - `file`: 要追加内容的文件的路径，以字符串形式表示。
 /// - `contents`: 要追加到文件末尾的内容，以字符串形式表示。
+/// - `encoding`: 文件编码，需与文件原有内容一致，为空时使用utf-8。
 #[op2(fast)]
-pub fn op_fs_append(#[string] file: String, #[string] contents: String) -> Result<(), AnyError> {
+pub fn op_fs_append(
+    #[string] file: String,
+    #[string] contents: String,
+    #[string] encoding: String,
+) -> Result<(), AnyError> {
+    let Some(bytes) = prepare_append_text(&file, &contents, &encoding)? else {
+        return Ok(());
+    };
     // 如果文件不存在，则创建文件
     create_file_if_not_exists(&file)?;
     let file_opt = std::fs::OpenOptions::new().append(true).open(&file);
     match file_opt {
-        Ok(mut file) => match file.write_all(contents.as_bytes()) {
+        Ok(mut file) => match file.write_all(&bytes) {
             Ok(_) => {
                 return Ok(());
             }
@@ -225,6 +354,9 @@ pub fn op_fs_append(#[string] file: String, #[string] contents: String) -> Resul
 /// - `path`: 要创建或截断的文件的路径，以字符串形式表示。
 #[op2(fast)]
 pub fn op_fs_create_file(#[string] path: String) -> Result<(), AnyError> {
+    if prepare_write(Operation::CreateFile, &path, Some(0))? {
+        return Ok(());
+    }
     let file = std::fs::File::create(path);
     match file {
         Ok(_) => {
@@ -235,3 +367,152 @@ pub fn op_fs_create_file(#[string] path: String) -> Result<(), AnyError> {
         }
     }
 }
+
+// 递归遍历目录，返回目录下的文件和子目录，按名称排序
+/// # 参数
+/// - `root`: 要遍历的目录的路径，以字符串形式表示。
+/// - `options`: 遍历选项，包括 include、exclude 通配符、最大深度和类型。
+#[op2]
+#[serde]
+pub fn op_fs_walk(
+    #[string] root: String,
+    #[serde] options: WalkOptions,
+) -> Result<Vec<FileEntry>, AnyError> {
+    check_read(&root)?;
+    check_follow_links(options.follow_links)?;
+    walk(&root, &options)
+}
+
+// 查找与通配符匹配的文件和目录，如 C:/share/**/*.xlsx，相对路径从当前工作目录开始
+/// # 参数
+/// - `pattern`: 通配符路径，以字符串形式表示。
+/// - `options`: 遍历选项，include 由 pattern 决定。
+#[op2]
+#[serde]
+pub fn op_fs_glob(
+    #[string] pattern: String,
+    #[serde] options: WalkOptions,
+) -> Result<Vec<FileEntry>, AnyError> {
+    // 只检查通配符之前的目录，匹配的文件都在该目录下
+    check_read(&split_glob(&pattern).0.to_string_lossy())?;
+    check_follow_links(options.follow_links)?;
+    let cwd = std::env::current_dir()?;
+    glob(&pattern, &cwd.to_string_lossy(), &options)
+}
+
+// 读取文件的全部字节
+/// # 参数
+/// - `path`: 要读取的文件的路径，以字符串形式表示。
+#[op2]
+#[buffer]
+pub fn op_fs_read_bytes(#[string] path: String) -> Result<Vec<u8>, AnyError> {
+    check_read(&path)?;
+    Ok(fs::read(path)?)
+}
+
+// 读取文件从指定位置开始的字节，超出文件末尾的部分不返回
+/// # 参数
+/// - `path`: 要读取的文件的路径，以字符串形式表示。
+/// - `offset`: 开始读取的字节位置，从0开始。
+/// - `length`: 最多读取的字节数。
+#[op2]
+#[buffer]
+pub fn op_fs_read_range(
+    #[string] path: String,
+    #[number] offset: u64,
+    #[number] length: u64,
+) -> Result<Vec<u8>, AnyError> {
+    check_read(&path)?;
+    let mut file = fs::File::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut buf = Vec::new();
+    file.take(length).read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+// 将字节写入文件，会覆盖原有内容
+/// # 参数
+/// - `path`: 要写入的文件的路径，以字符串形式表示。
+/// - `data`: 要写入的字节。
+#[op2(fast)]
+pub fn op_fs_write_bytes(#[string] path: String, #[buffer] data: &[u8]) -> Result<(), AnyError> {
+    if prepare_write(Operation::Write, &path, Some(data.len() as u64))? {
+        return Ok(());
+    }
+    create_file_if_not_exists(&path)?;
+    fs::write(path, data)?;
+    Ok(())
+}
+
+// 追加字节到文件末尾
+/// # 参数
+/// - `path`: 要追加内容的文件的路径，以字符串形式表示。
+/// - `data`: 要追加的字节。
+#[op2(fast)]
+pub fn op_fs_append_bytes(#[string] path: String, #[buffer] data: &[u8]) -> Result<(), AnyError> {
+    if prepare_write(Operation::Append, &path, Some(data.len() as u64))? {
+        return Ok(());
+    }
+    create_file_if_not_exists(&path)?;
+    let mut file = fs::OpenOptions::new().append(true).open(&path)?;
+    file.write_all(data)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use deno_core::error::get_custom_error_class;
+
+    use super::*;
+    use crate::deno::{
+        dry_run::{finish_dry_run, start_dry_run},
+        permissions::{set_permissions, Permissions},
+    };
+
+    fn denied<T>(result: Result<T, AnyError>) -> bool {
+        result.is_err_and(|err| get_custom_error_class(&err) == Some("PermissionDenied"))
+    }
+
+    #[test]
+    fn prepare_checks_permissions_and_dry_run() {
+        let root = std::env::temp_dir().join(format!("xls-dsl-prepare-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("in")).unwrap();
+        std::fs::write(root.join("in/a.txt"), "abc").unwrap();
+        set_permissions(Some(Permissions::new(
+            vec![root.join("in")],
+            vec![root.join("out")],
+            root.join("out"),
+            false,
+        )));
+        let path = |name: &str| root.join(name).to_string_lossy().into_owned();
+
+        assert!(denied(prepare_write(
+            Operation::Remove,
+            &path("in/a.txt"),
+            None
+        )));
+        assert!(denied(prepare_write_text(&path("in/b.txt"), "x", "")));
+        assert!(denied(prepare_append_text(&path("in/a.txt"), "x", "")));
+        assert!(denied(prepare_copy(&path("in/a.txt"), &path("b.txt"))));
+        assert!(denied(prepare_rename(
+            &path("in/a.txt"),
+            &path("out/a.txt")
+        )));
+        assert!(!prepare_copy(&path("in/a.txt"), &path("out/a.txt")).unwrap());
+        let bytes = prepare_write_text(&path("out/b.txt"), "abc", "utf-8-bom").unwrap();
+        assert_eq!(bytes.unwrap().len(), 6);
+
+        // 试运行只记录，不需要实际执行
+        start_dry_run();
+        assert!(prepare_copy(&path("in/a.txt"), &path("out/a.txt")).unwrap());
+        assert!(prepare_append_text(&path("out/a.txt"), "def", "")
+            .unwrap()
+            .is_none());
+        let report = finish_dry_run().unwrap();
+        assert_eq!(report.changes.len(), 2);
+        assert_eq!(report.bytes, 6);
+
+        set_permissions(None);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
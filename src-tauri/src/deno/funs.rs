@@ -1,14 +1,32 @@
 use handlebars::Handlebars;
 use sonyflake::Sonyflake;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::sync::Mutex;
 use tera::Context;
+use tokio::sync::mpsc::Receiver;
 
-use deno_core::{error::AnyError, extension, op2};
+use deno_core::{
+    error::AnyError, extension, op2, AsyncRefCell, OpState, RcRef, Resource, ResourceId,
+};
 
 use crate::{
     dao::models::RunLog,
-    deno::{fs_funs, lib::{emit_log, XLS_PATH}},
-    parse_xls::lib::ParseXls,
+    deno::{
+        dry_run::{plan, Operation},
+        fs_async_funs, fs_funs,
+        lib::{emit_log, XLS_PATH},
+        path_funs,
+        permissions::{check_read, check_write},
+        xlsx_funs,
+    },
+    parse_xls::{
+        delimited::{csv_bytes, read_csv, write_csv, CsvOptions, CsvWriteOptions},
+        lib::{DefinedName, JsonObject, ParseXls, ReadOptions, SheetData, TableInfo},
+        reference::{column_name, column_number, MAX_COLUMNS},
+        stream::{stream_rows, RowChunk},
+    },
 };
 
 lazy_static::lazy_static! {
@@ -23,6 +41,7 @@ async fn op_read_xls(#[string] mut path: String) -> Result<serde_json::Value, An
     if path == "" {
         path = XLS_PATH.with(|path| path.borrow().clone());
     }
+    check_read(&path)?;
 
     let mut parse = ParseXls { xls_path: path };
 
@@ -38,6 +57,161 @@ async fn op_read_xls(#[string] mut path: String) -> Result<serde_json::Value, An
     }
 }
 
+#[op2(async)]
+#[serde]
+async fn op_read_xls_sheets(
+    #[string] mut path: String,
+    #[serde] options: ReadOptions,
+) -> Result<Vec<SheetData>, AnyError> {
+    if path.is_empty() {
+        path = XLS_PATH.with(|path| path.borrow().clone());
+    }
+    check_read(&path)?;
+
+    let mut parse = ParseXls { xls_path: path };
+    parse.read_sheets(&options)
+}
+
+#[op2(async)]
+#[serde]
+async fn op_read_xls_sheet(
+    #[string] mut path: String,
+    #[serde] options: ReadOptions,
+) -> Result<SheetData, AnyError> {
+    if path.is_empty() {
+        path = XLS_PATH.with(|path| path.borrow().clone());
+    }
+    check_read(&path)?;
+
+    let mut parse = ParseXls { xls_path: path };
+    parse.read_sheet(&options)
+}
+
+#[op2(async)]
+#[serde]
+async fn op_read_csv(
+    #[string] mut path: String,
+    #[serde] options: CsvOptions,
+) -> Result<SheetData, AnyError> {
+    if path.is_empty() {
+        path = XLS_PATH.with(|path| path.borrow().clone());
+    }
+    check_read(&path)?;
+
+    read_csv(&path, &options)
+}
+
+#[op2]
+#[smi]
+fn op_write_csv(
+    #[string] path: String,
+    #[serde] rows: Vec<Vec<serde_json::Value>>,
+    #[serde] options: CsvWriteOptions,
+) -> Result<u32, AnyError> {
+    check_write(&path)?;
+    let operation = if options.append {
+        Operation::Append
+    } else {
+        Operation::Write
+    };
+    let bytes = csv_bytes(&path, &rows, &options)?;
+    if !plan(operation, &path, None, Some(bytes.len() as u64)) {
+        write_csv(&path, &bytes, options.append)?;
+    }
+    Ok(rows.len() as u32)
+}
+
+#[op2(async)]
+#[serde]
+async fn op_read_xls_table(
+    #[string] mut path: String,
+    #[string] name: String,
+    #[serde] options: ReadOptions,
+) -> Result<SheetData, AnyError> {
+    if path.is_empty() {
+        path = XLS_PATH.with(|path| path.borrow().clone());
+    }
+    check_read(&path)?;
+
+    let mut parse = ParseXls { xls_path: path };
+    parse.read_table(&name, &options)
+}
+
+#[op2(async)]
+#[serde]
+async fn op_xls_tables(#[string] mut path: String) -> Result<Vec<TableInfo>, AnyError> {
+    if path.is_empty() {
+        path = XLS_PATH.with(|path| path.borrow().clone());
+    }
+    check_read(&path)?;
+
+    let mut parse = ParseXls { xls_path: path };
+    parse.tables()
+}
+
+#[op2(async)]
+#[serde]
+async fn op_xls_defined_names(#[string] mut path: String) -> Result<Vec<DefinedName>, AnyError> {
+    if path.is_empty() {
+        path = XLS_PATH.with(|path| path.borrow().clone());
+    }
+    check_read(&path)?;
+
+    let mut parse = ParseXls { xls_path: path };
+    parse.defined_names()
+}
+
+struct XlsStreamResource {
+    rx: AsyncRefCell<Receiver<RowChunk>>,
+}
+
+impl Resource for XlsStreamResource {
+    fn name(&self) -> Cow<'_, str> {
+        "xlsStream".into()
+    }
+}
+
+#[op2]
+#[smi]
+fn op_xls_stream_open(
+    state: &mut OpState,
+    #[string] mut path: String,
+    #[serde] options: ReadOptions,
+    chunk_size: u32,
+) -> Result<ResourceId, AnyError> {
+    if path.is_empty() {
+        path = XLS_PATH.with(|path| path.borrow().clone());
+    }
+    check_read(&path)?;
+
+    let rx = stream_rows(path, options, chunk_size as usize);
+    let rid = state.resource_table.add(XlsStreamResource {
+        rx: AsyncRefCell::new(rx),
+    });
+    Ok(rid)
+}
+
+#[op2(async)]
+#[serde]
+async fn op_xls_stream_next(
+    state: Rc<RefCell<OpState>>,
+    #[smi] rid: ResourceId,
+) -> Result<Option<Vec<JsonObject>>, AnyError> {
+    let resource = state
+        .borrow()
+        .resource_table
+        .get::<XlsStreamResource>(rid)?;
+    let mut rx = RcRef::map(&resource, |r| &r.rx).borrow_mut().await;
+    rx.recv().await.transpose()
+}
+
+#[op2(fast)]
+fn op_xls_stream_close(state: &mut OpState, #[smi] rid: ResourceId) -> Result<(), AnyError> {
+    // dropping the receiver stops the reader thread, closing twice is harmless
+    let _ = state.resource_table.take::<XlsStreamResource>(rid);
+    Ok(())
+}
+
 #[op2(fast)]
 fn println(#[string] str: String) -> Result<(), AnyError> {
     emit_log("println", RunLog::log(str));
@@ -57,6 +231,27 @@ fn op_md5(#[string] str: String) -> Result<String, AnyError> {
     Ok(res)
 }
 
+#[op2]
+#[string]
+fn op_column_name(index: u32) -> Result<String, AnyError> {
+    if index >= MAX_COLUMNS {
+        return Err(anyhow::anyhow!(
+            "column index {} out of range, expected 0..{}",
+            index,
+            MAX_COLUMNS - 1
+        ));
+    }
+    Ok(column_name(index))
+}
+
+#[op2(fast)]
+fn op_column_index(#[string] name: String) -> Result<u32, AnyError> {
+    match column_number(name.trim()) {
+        Some(index) if index < MAX_COLUMNS => Ok(index),
+        _ => Err(anyhow::anyhow!("invalid column name: {}, expected A..XFD", name)),
+    }
+}
+
 #[op2]
 #[string]
 fn op_uuid() -> Result<String, AnyError> {
@@ -98,7 +293,10 @@ fn handlebars_render(
 extension!(
     runjs,
     ops = [
-        println, eprintln, op_read_xls, op_md5, op_uuid, op_snowid,
+        println, eprintln, op_read_xls, op_read_xls_sheets, op_read_xls_sheet,
+        op_md5, op_uuid, op_snowid, op_column_name, op_column_index,
+        op_xls_stream_open, op_xls_stream_next, op_xls_stream_close,
+        op_read_xls_table, op_xls_tables, op_xls_defined_names, op_read_csv, op_write_csv,
         fs_funs::op_fs_copy_file,
         fs_funs::op_fs_create_dir,
         fs_funs::op_fs_read_dir,
@@ -116,6 +314,58 @@ extension!(
         fs_funs::op_fs_read_line,
         fs_funs::op_fs_append,
         fs_funs::op_fs_create_file,
+        fs_funs::op_fs_read_bytes,
+        fs_funs::op_fs_read_range,
+        fs_funs::op_fs_write_bytes,
+        fs_funs::op_fs_append_bytes,
+        fs_funs::op_fs_walk,
+        fs_funs::op_fs_glob,
+        fs_funs::op_fs_stat,
+        fs_async_funs::op_fs_copy_file_async,
+        fs_async_funs::op_fs_create_dir_async,
+        fs_async_funs::op_fs_create_dir_all_async,
+        fs_async_funs::op_fs_exists_async,
+        fs_async_funs::op_fs_hard_link_async,
+        fs_async_funs::op_fs_read_to_string_async,
+        fs_async_funs::op_fs_read_dir_async,
+        fs_async_funs::op_fs_is_dir_async,
+        fs_async_funs::op_fs_is_file_async,
+        fs_async_funs::op_fs_remove_dir_async,
+        fs_async_funs::op_fs_remove_dir_all_async,
+        fs_async_funs::op_fs_remove_file_async,
+        fs_async_funs::op_fs_rename_async,
+        fs_async_funs::op_fs_write_async,
+        fs_async_funs::op_fs_read_line_async,
+        fs_async_funs::op_fs_append_async,
+        fs_async_funs::op_fs_create_file_async,
+        fs_async_funs::op_fs_read_bytes_async,
+        fs_async_funs::op_fs_read_range_async,
+        fs_async_funs::op_fs_write_bytes_async,
+        fs_async_funs::op_fs_append_bytes_async,
+        fs_async_funs::op_fs_walk_async,
+        fs_async_funs::op_fs_glob_async,
+        fs_async_funs::op_fs_stat_async,
+        path_funs::op_path_sep,
+        path_funs::op_path_join,
+        path_funs::op_path_resolve,
+        path_funs::op_path_normalize,
+        path_funs::op_path_dirname,
+        path_funs::op_path_basename,
+        path_funs::op_path_extname,
+        path_funs::op_path_stem,
+        path_funs::op_path_relative,
+        path_funs::op_path_user_dir,
+        path_funs::op_path_output_dir,
+        xlsx_funs::op_xlsx_new,
+        xlsx_funs::op_xlsx_add_sheet,
+        xlsx_funs::op_xlsx_write_rows,
+        xlsx_funs::op_xlsx_set_columns,
+        xlsx_funs::op_xlsx_set_row,
+        xlsx_funs::op_xlsx_freeze_panes,
+        xlsx_funs::op_xlsx_autofilter,
+        xlsx_funs::op_xlsx_save,
+        xlsx_funs::op_xlsx_close,
+        xlsx_funs::op_xlsx_fill_template,
         op_tera_template,
         handlebars_render
     ],
@@ -1,17 +1,25 @@
 use deno_core::error::AnyError;
+use deno_core::futures::future::{self, Either};
 use deno_core::url::Url;
+use deno_core::v8::{self, IsolateHandle};
 use lazy_static::lazy_static;
 use tauri::WebviewWindow;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
+use std::sync::mpsc;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Notify;
 
 use crate::dao::models::RunLog;
 use crate::dao::models::XlsFile;
 use tauri::Emitter;
 
+use super::dry_run::{finish_dry_run, start_dry_run};
 use super::funs::runjs;
+use super::permissions::{set_permissions, PermissionedLoader, Permissions};
 
 thread_local! {
     pub static XLS_PATH: RefCell<String> = RefCell::new(String::new());
@@ -19,6 +27,121 @@ thread_local! {
 
 lazy_static! {
     pub static ref WINDOW: Arc<Mutex<Option<WebviewWindow>>> = Arc::new(Mutex::new(None));
+    // 正在运行的脚本，键为运行id
+    static ref RUNS: Mutex<HashMap<String, RunState>> = Mutex::new(HashMap::new());
+}
+
+// 达到堆内存上限后额外给 V8 的内存，用于完成终止
+const HEAP_HEADROOM: usize = 32 * 1024 * 1024;
+
+// 脚本被终止的原因
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum StopReason {
+    Cancelled,
+    Timeout(Duration),
+    HeapLimit(usize),
+}
+
+#[derive(Default)]
+struct RunState {
+    // 脚本的 JsRuntime 创建后才有 isolate
+    isolate: Option<IsolateHandle>,
+    stopped: Option<StopReason>,
+    // 脚本已执行完，之后的取消和超时不再生效
+    finished: bool,
+    // 终止时通知事件循环，脚本在等待异步操作时 terminate_execution 不会生效
+    stop: Arc<Notify>,
+}
+
+// 脚本运行的限制
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RunLimits {
+    // 运行超时时间，为空时不限制
+    pub timeout: Option<Duration>,
+    // V8堆内存上限（MB），为空时使用 V8 的默认上限
+    pub max_heap_mb: Option<usize>,
+}
+
+impl RunLimits {
+    pub fn from_file(file: &XlsFile) -> Self {
+        Self {
+            timeout: file
+                .timeout_secs
+                .filter(|secs| *secs > 0)
+                .map(|secs| Duration::from_secs(secs as u64)),
+            max_heap_mb: file
+                .max_heap_mb
+                .filter(|mb| *mb > 0)
+                .map(|mb| mb as usize),
+        }
+    }
+}
+
+// 登记一次运行，在脚本开始前调用，使运行刚开始时也能取消
+pub(crate) fn start_run(run_id: &str) {
+    RUNS.lock().unwrap().insert(run_id.to_string(), RunState::default());
+}
+
+// 结束一次运行，返回脚本被终止的原因
+pub(crate) fn finish_run(run_id: &str) -> Option<StopReason> {
+    RUNS.lock()
+        .unwrap()
+        .remove(run_id)
+        .and_then(|run| run.stopped)
+}
+
+// 终止运行的 V8 isolate，只记录第一次终止的原因，运行不存在或已结束时返回 false
+fn stop_run(run_id: &str, reason: StopReason) -> bool {
+    match RUNS.lock().unwrap().get_mut(run_id) {
+        Some(run) if !run.finished => {
+            run.stopped.get_or_insert(reason);
+            if let Some(isolate) = &run.isolate {
+                isolate.terminate_execution();
+            }
+            run.stop.notify_one();
+            true
+        }
+        _ => false,
+    }
+}
+
+// 标记脚本已执行完，与 stop_run 在同一把锁下判断，结束时到达的超时不会再终止脚本
+fn end_script(run_id: &str) {
+    if let Some(run) = RUNS.lock().unwrap().get_mut(run_id) {
+        run.finished = true;
+        run.isolate = None;
+    }
+}
+
+// 取消运行，运行不存在或已结束时返回 false
+pub(crate) fn cancel_run(run_id: &str) -> bool {
+    stop_run(run_id, StopReason::Cancelled)
+}
+
+// 记录运行的 isolate，若在此之前已经取消则立即终止，返回运行终止时的通知
+fn attach_isolate(run_id: &str, isolate: IsolateHandle) -> Arc<Notify> {
+    match RUNS.lock().unwrap().get_mut(run_id) {
+        Some(run) => {
+            if run.stopped.is_some() {
+                isolate.terminate_execution();
+            }
+            run.isolate = Some(isolate);
+            run.stop.clone()
+        }
+        None => Arc::new(Notify::new()),
+    }
+}
+
+// 超时后终止运行，返回的 Sender 被丢弃（脚本结束）时监视线程退出
+fn watch_timeout(run_id: &str, timeout: Duration) -> mpsc::Sender<()> {
+    let (done, finished) = mpsc::channel::<()>();
+    let run_id = run_id.to_string();
+    std::thread::spawn(move || {
+        if let Err(mpsc::RecvTimeoutError::Timeout) = finished.recv_timeout(timeout) {
+            stop_run(&run_id, StopReason::Timeout(timeout));
+        }
+    });
+    done
 }
 
 pub(crate) fn emit_log(event: &str, log: RunLog) {
@@ -30,33 +153,78 @@ pub(crate) fn emit_log(event: &str, log: RunLog) {
 
 
 
-pub(crate) async fn run_js(code: String) -> Result<(), AnyError> {
-    let main_module = Url::parse("file://")?;
-
+pub(crate) async fn run_js(run_id: &str, code: String, limits: RunLimits) -> Result<(), AnyError> {
+    let create_params = limits
+        .max_heap_mb
+        .map(|mb| v8::CreateParams::default().heap_limits(0, mb * 1024 * 1024));
     let mut js_runtime = deno_core::JsRuntime::new(deno_core::RuntimeOptions {
-        module_loader: Some(Rc::new(deno_core::FsModuleLoader)),
+        module_loader: Some(Rc::new(PermissionedLoader)),
         extensions: vec![runjs::init_ops_and_esm()],
+        create_params,
         ..Default::default()
     });
+    let stop = attach_isolate(run_id, js_runtime.v8_isolate().thread_safe_handle());
+
+    // 接近堆内存上限时终止脚本而不是让整个程序崩溃，终止完成前每次都多给固定的内存
+    let heap_run_id = run_id.to_string();
+    js_runtime.add_near_heap_limit_callback(move |current, _initial| {
+        stop_run(&heap_run_id, StopReason::HeapLimit(current / (1024 * 1024)));
+        current + HEAP_HEADROOM
+    });
+    let timeout = limits.timeout.map(|timeout| watch_timeout(run_id, timeout));
+
+    let result = evaluate(&mut js_runtime, code, stop).await;
+    end_script(run_id);
+    drop(timeout);
+    result
+}
+
+// 加载并执行脚本，运行被终止时不等待未完成的异步操作，立即返回
+async fn evaluate(
+    js_runtime: &mut deno_core::JsRuntime,
+    code: String,
+    stop: Arc<Notify>,
+) -> Result<(), AnyError> {
+    let main_module = Url::parse("file://")?;
 
     let mod_id = js_runtime
         .load_main_es_module_from_code(&main_module, code)
         .await?;
 
     let result = js_runtime.mod_evaluate(mod_id);
-    js_runtime.run_event_loop(Default::default()).await?;
+    // 终止后不再驱动事件循环，丢弃 JsRuntime 时未完成的异步操作随之取消
+    let event_loop = js_runtime.run_event_loop(Default::default());
+    let stopped = stop.notified();
+    match future::select(Box::pin(event_loop), Box::pin(stopped)).await {
+        Either::Left((event_loop, _)) => event_loop?,
+        Either::Right(_) => return Err(anyhow::anyhow!("script was stopped")),
+    }
     result.await
 }
 
 pub struct DenoRuntime {
     file: XlsFile,
+    run_id: String,
+    // 试运行时文件修改只记录不执行，结束后输出变更报告
+    dry_run: bool,
 }
 
 impl DenoRuntime {
-    pub fn new(file: XlsFile) -> Self {
-        Self { file }
+    pub fn new(file: XlsFile, run_id: String) -> Self {
+        Self {
+            file,
+            run_id,
+            dry_run: false,
+        }
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
     }
-    pub async fn run_script(&self) -> Result<(), AnyError> {
+
+    // 返回脚本被终止的原因，正常结束或出错时为 None
+    pub(crate) async fn run_script(&self) -> Result<Option<StopReason>, AnyError> {
         // 执行脚本
         // 这里可以添加脚本执行的逻辑
         println!("Running script...");
@@ -66,18 +234,97 @@ impl DenoRuntime {
             *path = self.file.xlx_template.clone();
         });
 
-        let result = run_js(self.file.code.clone()).await;
+        // 脚本只能读写模板文件所在的目录、输出目录和脚本设置的目录
+        let permissions = Permissions::from_file(&self.file);
+        if self.dry_run {
+            start_dry_run();
+        } else {
+            let _ = std::fs::create_dir_all(permissions.output_dir());
+        }
+        set_permissions(Some(permissions));
+
+        let limits = RunLimits::from_file(&self.file);
+        let result = run_js(&self.run_id, self.file.code.clone(), limits).await;
+        set_permissions(None);
+
+        // 脚本出错时也输出出错之前计划的变更
+        if let Some(report) = finish_dry_run() {
+            emit_log(
+                "println",
+                RunLog::log(format!(
+                    "dry run: {} planned changes, {} bytes, nothing was written",
+                    report.changes.len(),
+                    report.bytes
+                )),
+            );
+            let json = serde_json::to_string_pretty(&report).unwrap_or_default();
+            emit_log("println", RunLog::report(json));
+        }
 
-        match result {
-            Ok(_) => {
+        // isolate 被终止时脚本以错误结束，按终止的原因报告
+        let stopped = finish_run(&self.run_id);
+        match (stopped, result) {
+            (Some(StopReason::Cancelled), _) => {
+                println!("JavaScript cancelled");
+                emit_log("println", RunLog::cancelled("run cancelled".to_string()));
+            }
+            (Some(StopReason::Timeout(timeout)), _) => {
+                let msg = format!("script timed out after {} seconds", timeout.as_secs());
+                eprintln!("{}", msg);
+                emit_log("println", RunLog::error(msg));
+            }
+            (Some(StopReason::HeapLimit(mb)), _) => {
+                let msg = format!("script ran out of memory, the heap limit is {} MB", mb);
+                eprintln!("{}", msg);
+                emit_log("println", RunLog::error(msg));
+            }
+            (None, Ok(_)) => {
                 println!("Successfully executed JavaScript");
                 emit_log("println", RunLog::result("".to_string()));
             }
-            Err(err) => {
+            (None, Err(err)) => {
                 eprintln!("Error executing JavaScript: {}", err);
                 emit_log("println", RunLog::error(format!("{:?}", err)));
             }
         }
-        Ok(())
+        Ok(stopped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stop_only_running_scripts() {
+        let timeout = StopReason::Timeout(Duration::from_secs(1));
+        start_run("running");
+        assert!(cancel_run("running"));
+        assert!(stop_run("running", timeout));
+        assert_eq!(finish_run("running"), Some(StopReason::Cancelled));
+
+        // a timeout arriving after the script ended doesn't mark the run
+        start_run("ended");
+        end_script("ended");
+        assert!(!stop_run("ended", timeout));
+        assert_eq!(finish_run("ended"), None);
+        assert!(!cancel_run("ended"));
+    }
+
+    #[test]
+    fn stop_at_heap_limit() {
+        let limits = RunLimits {
+            timeout: None,
+            max_heap_mb: Some(64),
+        };
+        let code = "const a = []; while (true) a.push(new Array(1e5).fill(1));";
+        start_run("heap");
+        actix_rt::System::new()
+            .block_on(run_js("heap", code.to_string(), limits))
+            .unwrap_err();
+        assert!(matches!(
+            finish_run("heap"),
+            Some(StopReason::HeapLimit(mb)) if mb >= 64
+        ));
     }
 }
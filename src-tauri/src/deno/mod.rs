@@ -1,3 +1,8 @@
+mod dry_run;
+mod fs_async_funs;
 mod fs_funs;
 mod funs;
+mod path_funs;
+mod permissions;
+mod xlsx_funs;
 pub(crate) mod lib;
@@ -0,0 +1,101 @@
+// 路径操作函数，只处理路径字符串，不访问文件系统（resolve 和 relative 使用当前工作目录）
+use deno_core::{error::AnyError, op2};
+
+use super::permissions;
+use crate::files::path;
+
+// 获取当前系统的路径分隔符，Windows 为 \，其他系统为 /
+#[op2]
+#[string]
+pub fn op_path_sep() -> String {
+    std::path::MAIN_SEPARATOR_STR.to_string()
+}
+
+// 拼接路径并规范化，忽略空字符串
+/// # 参数
+/// - `parts`: 要拼接的路径片段。
+#[op2]
+#[string]
+pub fn op_path_join(#[serde] parts: Vec<String>) -> String {
+    path::join(&parts)
+}
+
+// 从当前工作目录开始依次解析路径片段，返回绝对路径，绝对路径的片段会替换之前的结果
+/// # 参数
+/// - `parts`: 要解析的路径片段。
+#[op2]
+#[string]
+pub fn op_path_resolve(#[serde] parts: Vec<String>) -> Result<String, AnyError> {
+    Ok(path::resolve(&parts, &std::env::current_dir()?))
+}
+
+// 规范化路径，去掉 . 并解析 ..，分隔符转换为当前系统的分隔符
+/// # 参数
+/// - `path`: 要规范化的路径。
+#[op2]
+#[string]
+pub fn op_path_normalize(#[string] path: String) -> String {
+    path::normalize(&path)
+}
+
+// 获取路径所在的目录
+/// # 参数
+/// - `path`: 文件或目录的路径。
+#[op2]
+#[string]
+pub fn op_path_dirname(#[string] path: String) -> String {
+    path::dirname(&path)
+}
+
+// 获取路径的最后一部分，即文件名或目录名
+/// # 参数
+/// - `path`: 文件或目录的路径。
+#[op2]
+#[string]
+pub fn op_path_basename(#[string] path: String) -> String {
+    path::basename(&path)
+}
+
+// 获取文件的扩展名，包含点，如 .xlsx
+/// # 参数
+/// - `path`: 文件的路径。
+#[op2]
+#[string]
+pub fn op_path_extname(#[string] path: String) -> String {
+    path::extname(&path)
+}
+
+// 获取不包含扩展名的文件名
+/// # 参数
+/// - `path`: 文件的路径。
+#[op2]
+#[string]
+pub fn op_path_stem(#[string] path: String) -> String {
+    path::stem(&path)
+}
+
+// 获取从 from 到 to 的相对路径
+/// # 参数
+/// - `from`: 起始路径。
+/// - `to`: 目标路径。
+#[op2]
+#[string]
+pub fn op_path_relative(#[string] from: String, #[string] to: String) -> Result<String, AnyError> {
+    Ok(path::relative(&from, &to, &std::env::current_dir()?))
+}
+
+// 获取当前用户的目录
+/// # 参数
+/// - `name`: 目录名称，home、desktop、documents 或 downloads。
+#[op2]
+#[string]
+pub fn op_path_user_dir(#[string] name: String) -> Result<String, AnyError> {
+    path::user_dir(&name)
+}
+
+// 获取脚本的输出目录，输出目录总是可以读写
+#[op2]
+#[string]
+pub fn op_path_output_dir() -> String {
+    permissions::output_dir().to_string_lossy().to_string()
+}
@@ -0,0 +1,359 @@
+// 脚本的文件访问权限，每次运行只能读写允许的目录，默认为模板文件所在的目录和输出目录
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+};
+
+use deno_core::error::{custom_error, generic_error, AnyError};
+use deno_core::{
+    FsModuleLoader, ModuleLoadResponse, ModuleLoader, ModuleSpecifier, RequestedModuleType,
+    ResolutionKind,
+};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::{dao::models::XlsFile, deno::lib::WINDOW, files::path::normalize};
+
+thread_local! {
+    // 当前运行的权限，为空时不限制（不在脚本运行中）
+    static PERMISSIONS: RefCell<Option<Permissions>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Access {
+    Read,
+    Write,
+}
+
+impl Access {
+    fn name(&self) -> &'static str {
+        match self {
+            Access::Read => "read",
+            Access::Write => "write",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Permissions {
+    read: Vec<PathBuf>,
+    // 可写的目录同时可读
+    write: Vec<PathBuf>,
+    // 访问被拒绝时弹窗询问，允许后本次运行可以访问该目录
+    prompt: bool,
+    output_dir: PathBuf,
+}
+
+// 未设置输出目录时使用 ~/xlsDsl/output
+fn default_output_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_default()
+        .join("xlsDsl")
+        .join("output")
+}
+
+// 多行文本中的目录，一行一个，忽略空行
+fn dir_lines(text: &Option<String>) -> Vec<PathBuf> {
+    text.as_deref()
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+// 绝对路径，解析 . 和 ..，并通过已存在的最深一级目录解析符号链接，防止通过链接访问允许范围之外的文件
+fn resolve(path: &Path) -> PathBuf {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let path = PathBuf::from(normalize(&cwd.join(path).to_string_lossy()));
+    let mut existing = path.as_path();
+    let mut rest = Vec::new();
+    loop {
+        if let Ok(real) = existing.canonicalize() {
+            return rest.iter().rev().fold(real, |p, name| p.join(name));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => return path,
+        }
+    }
+}
+
+fn within(path: &Path, root: &Path) -> bool {
+    if cfg!(windows) {
+        let path = path.to_string_lossy().to_lowercase();
+        let root = root.to_string_lossy().to_lowercase();
+        Path::new(&path).starts_with(Path::new(&root))
+    } else {
+        path.starts_with(root)
+    }
+}
+
+impl Permissions {
+    pub fn new(read: Vec<PathBuf>, write: Vec<PathBuf>, output_dir: PathBuf, prompt: bool) -> Self {
+        Self {
+            read: read.iter().map(|p| resolve(p)).collect(),
+            write: write.iter().map(|p| resolve(p)).collect(),
+            prompt,
+            output_dir,
+        }
+    }
+
+    // 脚本的权限：模板文件所在的目录和输出目录可读写，另外加上脚本设置的目录
+    pub fn from_file(file: &XlsFile) -> Self {
+        let output_dir = file
+            .output_dir
+            .as_deref()
+            .map(str::trim)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(default_output_dir);
+        let mut write = vec![output_dir.clone()];
+        if let Some(dir) = Path::new(&file.xlx_template).parent() {
+            if !dir.as_os_str().is_empty() {
+                write.push(dir.to_path_buf());
+            }
+        }
+        write.extend(dir_lines(&file.write_dirs));
+        Self::new(
+            dir_lines(&file.read_dirs),
+            write,
+            output_dir,
+            file.prompt_access.unwrap_or(false),
+        )
+    }
+
+    pub fn output_dir(&self) -> &Path {
+        &self.output_dir
+    }
+
+    fn allows(&self, path: &Path, access: Access) -> bool {
+        let read: &[PathBuf] = match access {
+            Access::Read => &self.read,
+            Access::Write => &[],
+        };
+        self.write.iter().chain(read).any(|root| within(path, root))
+    }
+
+    fn grant(&mut self, dir: PathBuf, access: Access) {
+        match access {
+            Access::Read => self.read.push(dir),
+            Access::Write => self.write.push(dir),
+        }
+    }
+}
+
+// 设置当前运行的权限
+pub(crate) fn set_permissions(permissions: Option<Permissions>) {
+    PERMISSIONS.with(|p| *p.borrow_mut() = permissions);
+}
+
+// 当前运行的输出目录
+pub(crate) fn output_dir() -> PathBuf {
+    PERMISSIONS.with(|p| match p.borrow().as_ref() {
+        Some(permissions) => permissions.output_dir().to_path_buf(),
+        None => default_output_dir(),
+    })
+}
+
+// 弹窗询问是否允许访问，用户允许时返回 true
+fn ask(path: &Path, access: Access) -> bool {
+    let window = WINDOW.lock().unwrap().clone();
+    let verb = match access {
+        Access::Read => "读取",
+        Access::Write => "写入",
+    };
+    match window {
+        Some(window) => window
+            .dialog()
+            .message(format!(
+                "脚本请求{}以下路径，该路径不在允许的目录中：\n{}\n\n允许后本次运行可以{}该目录。",
+                verb,
+                path.display(),
+                verb,
+            ))
+            .title("文件访问权限")
+            .kind(MessageDialogKind::Warning)
+            .buttons(MessageDialogButtons::OkCancel)
+            .blocking_show(),
+        None => false,
+    }
+}
+
+// 检查当前运行是否可以访问路径，不允许时返回 PermissionDenied 错误
+pub(crate) fn check(path: &str, access: Access) -> Result<(), AnyError> {
+    let resolved = resolve(Path::new(path));
+    let prompt = PERMISSIONS.with(|p| match p.borrow().as_ref() {
+        None => Ok(()),
+        Some(permissions) if permissions.allows(&resolved, access) => Ok(()),
+        Some(permissions) => Err(permissions.prompt),
+    });
+    let prompt = match prompt {
+        Ok(()) => return Ok(()),
+        Err(prompt) => prompt,
+    };
+    // 弹窗期间不持有 PERMISSIONS 的借用
+    if prompt && ask(&resolved, access) {
+        let dir = if resolved.is_dir() {
+            resolved.clone()
+        } else {
+            resolved
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| resolved.clone())
+        };
+        PERMISSIONS.with(|p| {
+            if let Some(permissions) = p.borrow_mut().as_mut() {
+                permissions.grant(dir, access);
+            }
+        });
+        return Ok(());
+    }
+    Err(custom_error(
+        "PermissionDenied",
+        format!(
+            "permission denied: {} access to {} is not allowed, add its folder to the script's allowed folders",
+            access.name(),
+            path
+        ),
+    ))
+}
+
+pub(crate) fn check_read(path: &str) -> Result<(), AnyError> {
+    check(path, Access::Read)
+}
+
+pub(crate) fn check_write(path: &str) -> Result<(), AnyError> {
+    check(path, Access::Write)
+}
+
+// 硬链接与源文件共用同一份数据，通过链接写入会修改源文件，因此源文件也需要写权限
+pub(crate) fn check_hard_link(src: &str, dst: &str) -> Result<(), AnyError> {
+    check_write(src)?;
+    check_write(dst)
+}
+
+// 遍历目录时跟随符号链接可能进入允许范围之外的目录，脚本受限时不允许
+pub(crate) fn check_follow_links(follow_links: bool) -> Result<(), AnyError> {
+    let sandboxed = PERMISSIONS.with(|p| p.borrow().is_some());
+    if follow_links && sandboxed {
+        return Err(custom_error(
+            "PermissionDenied",
+            "permission denied: followLinks can't be used while the script's folder access is restricted",
+        ));
+    }
+    Ok(())
+}
+
+// 加载模块的 FsModuleLoader，脚本导入的本地文件同样要有读取权限
+pub(crate) struct PermissionedLoader;
+
+impl ModuleLoader for PermissionedLoader {
+    fn resolve(
+        &self,
+        specifier: &str,
+        referrer: &str,
+        kind: ResolutionKind,
+    ) -> Result<ModuleSpecifier, AnyError> {
+        FsModuleLoader.resolve(specifier, referrer, kind)
+    }
+
+    fn load(
+        &self,
+        module_specifier: &ModuleSpecifier,
+        maybe_referrer: Option<&ModuleSpecifier>,
+        is_dyn_import: bool,
+        requested_module_type: RequestedModuleType,
+    ) -> ModuleLoadResponse {
+        if module_specifier.scheme() == "file" {
+            let checked = match module_specifier.to_file_path() {
+                Ok(path) => check_read(&path.to_string_lossy()),
+                Err(_) => Err(generic_error(format!(
+                    "invalid module path: {}",
+                    module_specifier
+                ))),
+            };
+            if let Err(err) = checked {
+                return ModuleLoadResponse::Sync(Err(err));
+            }
+        }
+        FsModuleLoader.load(
+            module_specifier,
+            maybe_referrer,
+            is_dyn_import,
+            requested_module_type,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allowed_roots() {
+        let root = std::env::temp_dir().join(format!("xls-dsl-perm-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("in")).unwrap();
+        let permissions = Permissions::new(
+            vec![root.join("in")],
+            vec![root.join("out")],
+            root.join("out"),
+            false,
+        );
+        let allows = |path: PathBuf, access| permissions.allows(&resolve(&path), access);
+        assert!(allows(root.join("in/a.xlsx"), Access::Read));
+        assert!(!allows(root.join("in/a.xlsx"), Access::Write));
+        assert!(allows(root.join("out/new/b.csv"), Access::Write));
+        assert!(allows(root.join("out/b.csv"), Access::Read));
+        assert!(!allows(root.join("in/../secret.txt"), Access::Read));
+        assert!(!allows(root.join("inbox/a.xlsx"), Access::Read));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn refuse_hard_links_from_read_roots() {
+        let root = std::env::temp_dir().join(format!("xls-dsl-link-{}", std::process::id()));
+        set_permissions(Some(Permissions::new(
+            vec![root.join("in")],
+            vec![root.join("out")],
+            root.join("out"),
+            false,
+        )));
+        let path = |name: &str| root.join(name).to_string_lossy().into_owned();
+        assert!(check_hard_link(&path("in/a.xlsx"), &path("out/a.xlsx")).is_err());
+        assert!(check_hard_link(&path("out/a.xlsx"), &path("out/b.xlsx")).is_ok());
+        set_permissions(None);
+    }
+
+    #[test]
+    fn refuse_imports_outside_roots() {
+        let root = std::env::temp_dir().join(format!("xls-dsl-import-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("in")).unwrap();
+        std::fs::write(root.join("in/lib.js"), "export const a = 1;").unwrap();
+        std::fs::write(root.join("secret.js"), "export const b = 2;").unwrap();
+        set_permissions(Some(Permissions::new(
+            vec![root.join("in")],
+            vec![],
+            root.join("out"),
+            false,
+        )));
+
+        let load = |path: PathBuf| {
+            let specifier = ModuleSpecifier::from_file_path(path).unwrap();
+            match PermissionedLoader.load(&specifier, None, false, RequestedModuleType::None) {
+                ModuleLoadResponse::Sync(Err(err)) => Err(err.to_string()),
+                _ => Ok(()),
+            }
+        };
+        assert!(load(root.join("in/lib.js")).is_ok());
+        let err = load(root.join("secret.js")).unwrap_err();
+        assert!(err.starts_with("permission denied: read access"), "{}", err);
+
+        set_permissions(None);
+        assert!(load(root.join("secret.js")).is_ok());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,725 @@
+// xlsx 写入函数
+use std::{borrow::Cow, cell::RefCell};
+
+use anyhow::{anyhow, bail};
+use deno_core::{error::AnyError, op2, OpState, Resource, ResourceId};
+use rust_xlsxwriter::{
+    Color, Format, FormatAlign, FormatBorder, FormatUnderline, Formula, Workbook, Worksheet,
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    deno::{
+        dry_run::{self, plan, Operation},
+        lib::XLS_PATH,
+        permissions::{check_read, check_write},
+    },
+    parse_xls::{
+        lib::{parse_iso_date, JsonObject},
+        reference::{column_name, A1Range, MAX_COLUMNS},
+        template::{fill_template, FillReport},
+    },
+};
+
+/// excel sheets hold at most 1,048,576 rows
+const MAX_ROWS: u32 = 1_048_576;
+
+struct XlsxWriterResource {
+    workbook: RefCell<Workbook>,
+}
+
+impl Resource for XlsxWriterResource {
+    fn name(&self) -> Cow<'_, str> {
+        "xlsxWriter".into()
+    }
+}
+
+/// run `f` with the workbook of resource `rid`
+fn with_workbook<R>(
+    state: &mut OpState,
+    rid: ResourceId,
+    f: impl FnOnce(&mut Workbook) -> anyhow::Result<R>,
+) -> anyhow::Result<R> {
+    let resource = state.resource_table.get::<XlsxWriterResource>(rid)?;
+    let mut workbook = resource.workbook.borrow_mut();
+    f(&mut workbook)
+}
+
+/// Cell style sent by scripts, unset fields keep the excel default
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CellStyle {
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub font_name: Option<String>,
+    pub font_size: Option<f64>,
+    /// `#RRGGBB`
+    pub font_color: Option<String>,
+    /// excel number format such as `#,##0.00`, `0%` or `yyyy-mm-dd`
+    pub num_format: Option<String>,
+    /// solid background color, `#RRGGBB`
+    pub fill: Option<String>,
+    pub border: Option<BorderStyle>,
+    pub border_color: Option<String>,
+    pub align: Option<HAlign>,
+    pub valign: Option<VAlign>,
+    pub wrap: bool,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BorderStyle {
+    Thin,
+    Medium,
+    Thick,
+    Dashed,
+    Dotted,
+    Double,
+    Hair,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HAlign {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum VAlign {
+    Top,
+    Center,
+    Bottom,
+}
+
+/// parse `#RRGGBB` or `RRGGBB`, rust_xlsxwriter only prints a warning for bad colors
+fn parse_color(s: &str) -> anyhow::Result<Color> {
+    let hex = s.strip_prefix('#').unwrap_or(s);
+    match u32::from_str_radix(hex, 16) {
+        Ok(rgb) if hex.len() == 6 => Ok(Color::RGB(rgb)),
+        _ => bail!("invalid color: {}, expected #RRGGBB", s),
+    }
+}
+
+impl CellStyle {
+    fn to_format(&self) -> anyhow::Result<Format> {
+        let mut format = Format::new();
+        if self.bold {
+            format = format.set_bold();
+        }
+        if self.italic {
+            format = format.set_italic();
+        }
+        if self.underline {
+            format = format.set_underline(FormatUnderline::Single);
+        }
+        if let Some(name) = &self.font_name {
+            format = format.set_font_name(name);
+        }
+        if let Some(size) = self.font_size {
+            format = format.set_font_size(size);
+        }
+        if let Some(color) = &self.font_color {
+            format = format.set_font_color(parse_color(color)?);
+        }
+        if let Some(num_format) = &self.num_format {
+            format = format.set_num_format(num_format);
+        }
+        if let Some(fill) = &self.fill {
+            format = format.set_background_color(parse_color(fill)?);
+        }
+        if let Some(border) = self.border {
+            format = format.set_border(match border {
+                BorderStyle::Thin => FormatBorder::Thin,
+                BorderStyle::Medium => FormatBorder::Medium,
+                BorderStyle::Thick => FormatBorder::Thick,
+                BorderStyle::Dashed => FormatBorder::Dashed,
+                BorderStyle::Dotted => FormatBorder::Dotted,
+                BorderStyle::Double => FormatBorder::Double,
+                BorderStyle::Hair => FormatBorder::Hair,
+            });
+        }
+        if let Some(color) = &self.border_color {
+            format = format.set_border_color(parse_color(color)?);
+        }
+        if let Some(align) = self.align {
+            format = format.set_align(match align {
+                HAlign::Left => FormatAlign::Left,
+                HAlign::Center => FormatAlign::Center,
+                HAlign::Right => FormatAlign::Right,
+            });
+        }
+        if let Some(valign) = self.valign {
+            format = format.set_align(match valign {
+                VAlign::Top => FormatAlign::Top,
+                VAlign::Center => FormatAlign::VerticalCenter,
+                VAlign::Bottom => FormatAlign::Bottom,
+            });
+        }
+        if self.wrap {
+            format = format.set_text_wrap();
+        }
+        Ok(format)
+    }
+}
+
+/// Formats used by one write call, dates fall back to a date number format
+/// unless the style sets its own
+struct CellFormats {
+    cell: Option<Format>,
+    date: Format,
+    datetime: Format,
+}
+
+impl CellFormats {
+    fn new(style: Option<&CellStyle>) -> anyhow::Result<Self> {
+        let cell = style.map(CellStyle::to_format).transpose()?;
+        let date_format = |num_format: &str| match (&cell, style) {
+            (Some(f), Some(s)) if s.num_format.is_some() => f.clone(),
+            (Some(f), _) => f.clone().set_num_format(num_format),
+            (None, _) => Format::new().set_num_format(num_format),
+        };
+        Ok(CellFormats {
+            date: date_format("yyyy-mm-dd"),
+            datetime: date_format("yyyy-mm-dd hh:mm:ss"),
+            cell,
+        })
+    }
+}
+
+/// write one json value with the matching excel cell type, `null` leaves the
+/// cell empty but still applies the style, e.g. borders around blank cells
+fn write_value(
+    sheet: &mut Worksheet,
+    row: u32,
+    col: u16,
+    value: &Value,
+    formats: &CellFormats,
+) -> anyhow::Result<()> {
+    let format = formats.cell.as_ref();
+    match value {
+        Value::Null => {
+            if let Some(f) = format {
+                sheet.write_blank(row, col, f)?;
+            }
+        }
+        Value::Bool(b) => match format {
+            Some(f) => {
+                sheet.write_boolean_with_format(row, col, *b, f)?;
+            }
+            None => {
+                sheet.write_boolean(row, col, *b)?;
+            }
+        },
+        Value::Number(n) => {
+            let n = n.as_f64().ok_or_else(|| anyhow!("invalid number: {}", n))?;
+            match format {
+                Some(f) => sheet.write_number_with_format(row, col, n, f)?,
+                None => sheet.write_number(row, col, n)?,
+            };
+        }
+        Value::String(s) => {
+            match format {
+                Some(f) => sheet.write_string_with_format(row, col, s, f)?,
+                None => sheet.write_string(row, col, s)?,
+            };
+        }
+        Value::Object(obj) => {
+            if let Some(date) = obj.get("$date").and_then(Value::as_str) {
+                let dt = parse_iso_date(date)?;
+                let format = if dt.time() == chrono::NaiveTime::MIN {
+                    &formats.date
+                } else {
+                    &formats.datetime
+                };
+                sheet.write_datetime_with_format(row, col, dt, format)?;
+            } else if let Some(formula) = obj.get("formula").and_then(Value::as_str) {
+                let mut formula = Formula::new(formula);
+                // the cached result shown by viewers that don't recalculate
+                match obj.get("value") {
+                    Some(Value::String(s)) => formula = formula.set_result(s),
+                    Some(Value::Number(n)) => formula = formula.set_result(n.to_string()),
+                    Some(Value::Bool(b)) => {
+                        formula = formula.set_result(if *b { "TRUE" } else { "FALSE" })
+                    }
+                    _ => {}
+                }
+                match format {
+                    Some(f) => sheet.write_formula_with_format(row, col, formula, f)?,
+                    None => sheet.write_formula(row, col, formula)?,
+                };
+            } else {
+                bail!(
+                    "unsupported cell value at {}{}: {}",
+                    column_name(col as u32),
+                    row + 1,
+                    value
+                );
+            }
+        }
+        Value::Array(_) => bail!(
+            "unsupported cell value at {}{}: {}",
+            column_name(col as u32),
+            row + 1,
+            value
+        ),
+    }
+    Ok(())
+}
+
+/// add a sheet named `name`, `SheetN` when empty, returns its index. The sheet
+/// is only added once its name is valid, a rejected name must not leave a
+/// blank sheet in the workbook
+fn add_sheet(workbook: &mut Workbook, name: String) -> anyhow::Result<u32> {
+    // excel compares sheet names ignoring case, rust_xlsxwriter only checks on save
+    let names: Vec<String> = workbook
+        .worksheets()
+        .iter()
+        .map(|s| s.name().to_lowercase())
+        .collect();
+    let taken = |name: &str| names.contains(&name.to_lowercase());
+    let name = if name.is_empty() {
+        (names.len() + 1..)
+            .map(|n| format!("Sheet{}", n))
+            .find(|n| !taken(n))
+            .unwrap_or_default()
+    } else if taken(&name) {
+        bail!("sheet name already in use: {}", name);
+    } else {
+        name
+    };
+    let mut sheet = Worksheet::new();
+    sheet.set_name(name.as_str())?;
+    workbook.push_worksheet(sheet);
+    Ok(workbook.worksheets().len() as u32 - 1)
+}
+
+/// write `rows` starting at (`row`, `col`), cells past the xlsx limits are rejected
+fn write_rows(
+    worksheet: &mut Worksheet,
+    row: u32,
+    col: u32,
+    rows: &[Vec<Value>],
+    formats: &CellFormats,
+) -> anyhow::Result<()> {
+    for (i, cells) in rows.iter().enumerate() {
+        let r = row + i as u32;
+        if r >= MAX_ROWS {
+            bail!(
+                "row {} out of range, a sheet holds at most {} rows",
+                r + 1,
+                MAX_ROWS
+            );
+        }
+        for (j, value) in cells.iter().enumerate() {
+            let c = col + j as u32;
+            if c >= MAX_COLUMNS {
+                bail!(
+                    "column {} out of range, a sheet holds at most {} columns",
+                    c + 1,
+                    MAX_COLUMNS
+                );
+            }
+            write_value(worksheet, r, c as u16, value, formats)?;
+        }
+    }
+    Ok(())
+}
+
+// 新建一个空的 xlsx 工作簿，返回工作簿的资源 id
+#[op2(fast)]
+#[smi]
+pub fn op_xlsx_new(state: &mut OpState) -> Result<ResourceId, AnyError> {
+    let rid = state.resource_table.add(XlsxWriterResource {
+        workbook: RefCell::new(Workbook::new()),
+    });
+    Ok(rid)
+}
+
+// 向工作簿添加工作表，返回工作表序号（从0开始）
+/// # 参数
+/// - `rid`: 工作簿的资源 id。
+/// - `name`: 工作表名称，为空时使用默认名称 Sheet1、Sheet2...
+#[op2(fast)]
+pub fn op_xlsx_add_sheet(
+    state: &mut OpState,
+    #[smi] rid: ResourceId,
+    #[string] name: String,
+) -> Result<u32, AnyError> {
+    with_workbook(state, rid, |workbook| add_sheet(workbook, name))
+}
+
+// 从指定单元格开始写入多行数据
+/// # 参数
+/// - `rid`: 工作簿的资源 id。
+/// - `sheet`: 工作表序号，从0开始。
+/// - `row`: 起始行号，从0开始。
+/// - `col`: 起始列号，从0开始。
+/// - `rows`: 二维数组，单元格可以是数字、字符串、布尔值、null、{"$date": "..."} 或 {"formula": "..."}。
+/// - `style`: 可选的单元格样式，应用到写入的每个单元格。
+#[op2]
+pub fn op_xlsx_write_rows(
+    state: &mut OpState,
+    #[smi] rid: ResourceId,
+    sheet: u32,
+    row: u32,
+    col: u32,
+    #[serde] rows: Vec<Vec<Value>>,
+    #[serde] style: Option<CellStyle>,
+) -> Result<(), AnyError> {
+    let formats = CellFormats::new(style.as_ref())?;
+    with_workbook(state, rid, |workbook| {
+        let worksheet = workbook.worksheet_from_index(sheet as usize)?;
+        write_rows(worksheet, row, col, &rows, &formats)
+    })
+}
+
+/// Column settings sent by `sheet.set_column`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ColumnOptions {
+    /// width in characters, excel's default is 8.43
+    pub width: Option<f64>,
+    /// default style of the cells in the column that have no style of their own
+    pub style: Option<CellStyle>,
+    pub hidden: bool,
+}
+
+/// Row settings sent by `sheet.set_row`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RowOptions {
+    /// height in points, excel's default is 15
+    pub height: Option<f64>,
+    pub style: Option<CellStyle>,
+    pub hidden: bool,
+}
+
+// 设置一列或连续多列的宽度、默认样式和是否隐藏
+/// # 参数
+/// - `rid`: 工作簿的资源 id。
+/// - `sheet`: 工作表序号，从0开始。
+/// - `first_col`: 起始列号，从0开始。
+/// - `last_col`: 结束列号，从0开始，包含该列。
+/// - `options`: 列宽、样式和是否隐藏。
+#[op2]
+pub fn op_xlsx_set_columns(
+    state: &mut OpState,
+    #[smi] rid: ResourceId,
+    sheet: u32,
+    first_col: u32,
+    last_col: u32,
+    #[serde] options: ColumnOptions,
+) -> Result<(), AnyError> {
+    if first_col > last_col || last_col >= MAX_COLUMNS {
+        bail!("invalid column range: {}..{}", first_col, last_col);
+    }
+    let format = options
+        .style
+        .as_ref()
+        .map(CellStyle::to_format)
+        .transpose()?;
+    with_workbook(state, rid, |workbook| {
+        let worksheet = workbook.worksheet_from_index(sheet as usize)?;
+        for col in first_col as u16..=last_col as u16 {
+            if let Some(width) = options.width {
+                worksheet.set_column_width(col, width)?;
+            }
+            if let Some(format) = &format {
+                worksheet.set_column_format(col, format)?;
+            }
+            if options.hidden {
+                worksheet.set_column_hidden(col)?;
+            }
+        }
+        Ok(())
+    })
+}
+
+// 设置行高、行的默认样式和是否隐藏
+/// # 参数
+/// - `rid`: 工作簿的资源 id。
+/// - `sheet`: 工作表序号，从0开始。
+/// - `row`: 行号，从0开始。
+/// - `options`: 行高、样式和是否隐藏。
+#[op2]
+pub fn op_xlsx_set_row(
+    state: &mut OpState,
+    #[smi] rid: ResourceId,
+    sheet: u32,
+    row: u32,
+    #[serde] options: RowOptions,
+) -> Result<(), AnyError> {
+    let format = options
+        .style
+        .as_ref()
+        .map(CellStyle::to_format)
+        .transpose()?;
+    with_workbook(state, rid, |workbook| {
+        let worksheet = workbook.worksheet_from_index(sheet as usize)?;
+        if let Some(height) = options.height {
+            worksheet.set_row_height(row, height)?;
+        }
+        if let Some(format) = &format {
+            worksheet.set_row_format(row, format)?;
+        }
+        if options.hidden {
+            worksheet.set_row_hidden(row)?;
+        }
+        Ok(())
+    })
+}
+
+// 冻结窗格，指定单元格上方的行和左侧的列在滚动时保持可见
+/// # 参数
+/// - `rid`: 工作簿的资源 id。
+/// - `sheet`: 工作表序号，从0开始。
+/// - `row`: 冻结的行数，1 表示冻结第一行，0 表示不冻结行。
+/// - `col`: 冻结的列数，1 表示冻结第一列，0 表示不冻结列。
+#[op2(fast)]
+pub fn op_xlsx_freeze_panes(
+    state: &mut OpState,
+    #[smi] rid: ResourceId,
+    sheet: u32,
+    row: u32,
+    col: u32,
+) -> Result<(), AnyError> {
+    if col >= MAX_COLUMNS {
+        bail!("column {} out of range", col);
+    }
+    with_workbook(state, rid, |workbook| {
+        workbook
+            .worksheet_from_index(sheet as usize)?
+            .set_freeze_panes(row, col as u16)?;
+        Ok(())
+    })
+}
+
+// 为单元格区域添加自动筛选，区域的第一行作为筛选表头
+/// # 参数
+/// - `rid`: 工作簿的资源 id。
+/// - `sheet`: 工作表序号，从0开始。
+/// - `range`: A1 格式的单元格区域，如 "A1:F100"。
+#[op2(fast)]
+pub fn op_xlsx_autofilter(
+    state: &mut OpState,
+    #[smi] rid: ResourceId,
+    sheet: u32,
+    #[string] range: String,
+) -> Result<(), AnyError> {
+    let r = A1Range::parse(&range)?;
+    if r.end.0 >= MAX_ROWS || r.end.1 >= MAX_COLUMNS {
+        bail!("autofilter range must be bounded, e.g. A1:F100: {}", range);
+    }
+    with_workbook(state, rid, |workbook| {
+        workbook.worksheet_from_index(sheet as usize)?.autofilter(
+            r.start.0,
+            r.start.1 as u16,
+            r.end.0,
+            r.end.1 as u16,
+        )?;
+        Ok(())
+    })
+}
+
+// 保存工作簿到指定路径，若文件已存在则覆盖
+/// # 参数
+/// - `rid`: 工作簿的资源 id。
+/// - `path`: 保存的 xlsx 文件路径。
+#[op2(fast)]
+pub fn op_xlsx_save(
+    state: &mut OpState,
+    #[smi] rid: ResourceId,
+    #[string] path: String,
+) -> Result<(), AnyError> {
+    check_write(&path)?;
+    with_workbook(state, rid, |workbook| {
+        if workbook.worksheets().is_empty() {
+            workbook.add_worksheet();
+        }
+        if dry_run::is_dry_run() {
+            let bytes = workbook
+                .save_to_buffer()
+                .map_err(|e| anyhow!("failed to save workbook {}: {}", path, e))?;
+            plan(Operation::Write, &path, None, Some(bytes.len() as u64));
+            return Ok(());
+        }
+        workbook
+            .save(&path)
+            .map_err(|e| anyhow!("failed to save workbook {}: {}", path, e))
+    })
+}
+
+// 填充已有的 xlsx 模板并另存为新文件，保留模板的样式、公式、图表和其他工作表
+/// # 参数
+/// - `src`: 模板文件路径，为空时使用选择的文件。
+/// - `dst`: 保存的文件路径，不能与模板相同。
+/// - `values`: 键为定义的名称或单元格中 {{key}} 占位符的键，值为要填入的数据。
+#[op2]
+#[serde]
+pub fn op_xlsx_fill_template(
+    #[string] mut src: String,
+    #[string] dst: String,
+    #[serde] values: JsonObject,
+) -> Result<FillReport, AnyError> {
+    if src.is_empty() {
+        src = XLS_PATH.with(|path| path.borrow().clone());
+    }
+    check_read(&src)?;
+    check_write(&dst)?;
+    let (report, bytes) = fill_template(&src, &dst, &values)?;
+    if !plan(Operation::Write, &dst, None, Some(bytes.len() as u64)) {
+        std::fs::write(&dst, bytes)
+            .map_err(|e| anyhow!("failed to save workbook {}: {}", dst, e))?;
+    }
+    Ok(report)
+}
+
+// 释放工作簿占用的内存，未保存的内容会丢失
+/// # 参数
+/// - `rid`: 工作簿的资源 id。
+#[op2(fast)]
+pub fn op_xlsx_close(state: &mut OpState, #[smi] rid: ResourceId) -> Result<(), AnyError> {
+    let _ = state.resource_table.take::<XlsxWriterResource>(rid);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calamine::{open_workbook, Data, Reader, Xlsx};
+    use serde_json::json;
+
+    #[test]
+    fn reject_sheet_names_without_adding() {
+        let mut workbook = Workbook::new();
+        assert_eq!(add_sheet(&mut workbook, "Orders".to_string()).unwrap(), 0);
+        assert!(add_sheet(&mut workbook, "orders".to_string()).is_err());
+        assert!(add_sheet(&mut workbook, "a/b".to_string()).is_err());
+        assert!(add_sheet(&mut workbook, "x".repeat(32)).is_err());
+        assert_eq!(workbook.worksheets().len(), 1);
+
+        // default names skip the ones already taken
+        add_sheet(&mut workbook, "Sheet2".to_string()).unwrap();
+        assert_eq!(add_sheet(&mut workbook, String::new()).unwrap(), 2);
+        assert_eq!(workbook.worksheets()[2].name(), "Sheet3");
+    }
+
+    #[test]
+    fn write_and_read_back_cell_types() {
+        let mut workbook = Workbook::new();
+        add_sheet(&mut workbook, "Data".to_string()).unwrap();
+        let rows = vec![
+            vec![json!(1.5), json!(true), json!("text"), json!(null)],
+            vec![
+                json!({"$date": "2024-03-01"}),
+                json!({"$date": "2024-03-01T08:30:00"}),
+                json!({"formula": "=A1*2", "value": 3}),
+            ],
+        ];
+        let formats = CellFormats::new(None).unwrap();
+        let sheet = workbook.worksheet_from_index(0).unwrap();
+        write_rows(sheet, 0, 0, &rows, &formats).unwrap();
+        let err = write_value(sheet, 2, 0, &json!([1]), &formats).unwrap_err();
+        assert!(err.to_string().contains("unsupported cell value at A3"));
+
+        let path = std::env::temp_dir().join(format!("xls-dsl-writer-{}.xlsx", std::process::id()));
+        workbook.save(&path).unwrap();
+        let mut xlsx: Xlsx<_> = open_workbook(&path).unwrap();
+        let values = xlsx.worksheet_range("Data").unwrap();
+        let formulas = xlsx.worksheet_formula("Data").unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(values.get_value((0, 0)), Some(&Data::Float(1.5)));
+        assert_eq!(values.get_value((0, 1)), Some(&Data::Bool(true)));
+        assert_eq!(
+            values.get_value((0, 2)),
+            Some(&Data::String("text".to_string()))
+        );
+        // null without a style leaves the cell out
+        assert!(values.get_value((0, 3)).is_none_or(|d| *d == Data::Empty));
+        // dates are stored as serial numbers with a date format
+        match (values.get_value((1, 0)), values.get_value((1, 1))) {
+            (Some(Data::DateTime(date)), Some(Data::DateTime(datetime))) => {
+                assert_eq!(date.as_f64(), 45352.0);
+                assert!((datetime.as_f64() - (45352.0 + 8.5 / 24.0)).abs() < 1e-9);
+            }
+            other => panic!("expected dates, got {:?}", other),
+        }
+        assert_eq!(formulas.get_value((1, 2)), Some(&"A1*2".to_string()));
+        assert_eq!(values.get_value((1, 2)), Some(&Data::Float(3.0)));
+    }
+
+    #[test]
+    fn reject_cells_past_sheet_limits() {
+        let mut sheet = Worksheet::new();
+        let formats = CellFormats::new(None).unwrap();
+        let rows = vec![vec![json!(1)], vec![json!(2)]];
+        let err = write_rows(&mut sheet, MAX_ROWS - 1, 0, &rows, &formats).unwrap_err();
+        assert!(err.to_string().starts_with("row 1048577 out of range"));
+        let row = vec![vec![json!(1), json!(2)]];
+        let err = write_rows(&mut sheet, 0, MAX_COLUMNS - 1, &row, &formats).unwrap_err();
+        assert!(err.to_string().starts_with("column 16385 out of range"));
+    }
+
+    #[test]
+    fn parse_colors() {
+        assert_eq!(parse_color("#1F4E78").unwrap(), Color::RGB(0x1F4E78));
+        assert_eq!(parse_color("ffeedd").unwrap(), Color::RGB(0xFFEEDD));
+        assert!(parse_color("#12345").is_err());
+        assert!(parse_color("red").is_err());
+    }
+
+    #[test]
+    fn style_to_format() {
+        let style: CellStyle = serde_json::from_value(json!({
+            "bold": true,
+            "numFormat": "#,##0.00",
+            "fill": "#FFEEDD",
+            "border": "thin",
+            "align": "center",
+            "valign": "top",
+            "wrap": true,
+        }))
+        .unwrap();
+        let expected = Format::new()
+            .set_bold()
+            .set_num_format("#,##0.00")
+            .set_background_color(Color::RGB(0xFFEEDD))
+            .set_border(FormatBorder::Thin)
+            .set_align(FormatAlign::Center)
+            .set_align(FormatAlign::Top)
+            .set_text_wrap();
+        assert_eq!(style.to_format().unwrap(), expected);
+
+        let style: CellStyle = serde_json::from_value(json!({"fontColor": "blue"})).unwrap();
+        assert!(style.to_format().is_err());
+    }
+
+    #[test]
+    fn date_formats_fall_back() {
+        let formats = CellFormats::new(None).unwrap();
+        assert!(formats.cell.is_none());
+        assert_eq!(formats.date, Format::new().set_num_format("yyyy-mm-dd"));
+
+        // the style is kept and a date format added
+        let bold: CellStyle = serde_json::from_value(json!({"bold": true})).unwrap();
+        let formats = CellFormats::new(Some(&bold)).unwrap();
+        assert_eq!(
+            formats.datetime,
+            Format::new()
+                .set_bold()
+                .set_num_format("yyyy-mm-dd hh:mm:ss")
+        );
+
+        // a number format in the style wins
+        let custom: CellStyle = serde_json::from_value(json!({"numFormat": "dd/mm/yyyy"})).unwrap();
+        let formats = CellFormats::new(Some(&custom)).unwrap();
+        assert_eq!(formats.date, Format::new().set_num_format("dd/mm/yyyy"));
+        assert_eq!(formats.datetime, formats.date);
+    }
+}
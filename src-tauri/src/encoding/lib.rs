@@ -0,0 +1,181 @@
+use anyhow::bail;
+use encoding_rs::{Encoding, GB18030, UTF_16BE, UTF_16LE, UTF_8};
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+
+/// Text encoding chosen by a script, `None` label or `auto` means detect
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextEncoding {
+    pub encoding: &'static Encoding,
+    /// write a byte order mark, e.g. `utf-8-bom` so that excel recognises utf-8 csv
+    pub bom: bool,
+}
+
+impl TextEncoding {
+    pub const UTF8: TextEncoding = TextEncoding {
+        encoding: UTF_8,
+        bom: false,
+    };
+
+    /// parse a label such as `utf-8`, `utf-8-bom`, `gbk`, `gb18030` or
+    /// `utf-16le`, `None` for `auto` and empty labels
+    pub fn from_label(label: &str) -> anyhow::Result<Option<Self>> {
+        let label = label.trim().to_ascii_lowercase();
+        match label.as_str() {
+            "" | "auto" => return Ok(None),
+            "utf-8-bom" | "utf8-bom" | "utf-8-sig" | "utf8bom" => {
+                return Ok(Some(TextEncoding {
+                    encoding: UTF_8,
+                    bom: true,
+                }))
+            }
+            _ => {}
+        }
+        match Encoding::for_label(label.as_bytes()) {
+            // utf-16 written without an explicit byte order gets a bom, like notepad does
+            Some(encoding) => Ok(Some(TextEncoding {
+                encoding,
+                bom: label == "utf-16",
+            })),
+            None => bail!(
+                "unknown encoding: {}, expected utf-8, utf-8-bom, gbk, gb18030, utf-16le, utf-16be or auto",
+                label
+            ),
+        }
+    }
+
+    /// `TextEncoding::from_label` for an optional label
+    pub fn parse(label: Option<&str>) -> anyhow::Result<Option<Self>> {
+        match label {
+            Some(label) => TextEncoding::from_label(label),
+            None => Ok(None),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        if self.bom && self.encoding == UTF_8 {
+            "utf-8-bom".to_string()
+        } else {
+            self.encoding.name().to_ascii_lowercase()
+        }
+    }
+}
+
+/// guess the encoding of `bytes`: a byte order mark wins, then utf-16
+/// without bom, then utf-8, falling back to gb18030 (a superset of gbk)
+pub fn detect(bytes: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return encoding;
+    }
+    // ascii text stored as utf-16 has a zero in every other byte
+    let sample = &bytes[..bytes.len().min(4096) & !1];
+    if sample.len() >= 4 {
+        let zeros_at = |offset: usize| {
+            sample
+                .iter()
+                .skip(offset)
+                .step_by(2)
+                .filter(|b| **b == 0)
+                .count()
+        };
+        let half = sample.len() / 2;
+        if zeros_at(1) * 10 > half * 4 && zeros_at(0) * 10 < half {
+            return UTF_16LE;
+        }
+        if zeros_at(0) * 10 > half * 4 && zeros_at(1) * 10 < half {
+            return UTF_16BE;
+        }
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        return UTF_8;
+    }
+    GB18030
+}
+
+/// decode `bytes` with `encoding`, detecting it when `None`, a leading byte
+/// order mark is dropped, malformed input is an error rather than silently replaced
+pub fn decode(bytes: &[u8], encoding: Option<TextEncoding>) -> anyhow::Result<String> {
+    let encoding = match encoding {
+        Some(e) => e.encoding,
+        None => detect(bytes),
+    };
+    let (text, malformed) = encoding.decode_with_bom_removal(bytes);
+    if malformed {
+        bail!(
+            "the file is not valid {} text, pass the encoding it was saved with, e.g. \"gbk\" or \"utf-16le\"",
+            encoding.name()
+        );
+    }
+    Ok(text.into_owned())
+}
+
+/// encode `text`, characters the encoding can't represent are an error
+pub fn encode(text: &str, encoding: TextEncoding) -> anyhow::Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() + 3);
+    // encoding_rs only decodes utf-16, encoding it is done by hand
+    if encoding.encoding == UTF_16LE || encoding.encoding == UTF_16BE {
+        let le = encoding.encoding == UTF_16LE;
+        let units = std::iter::once(0xFEFF)
+            .filter(|_| encoding.bom)
+            .chain(text.encode_utf16());
+        for unit in units {
+            out.extend_from_slice(&if le {
+                unit.to_le_bytes()
+            } else {
+                unit.to_be_bytes()
+            });
+        }
+        return Ok(out);
+    }
+    if encoding.bom && encoding.encoding == UTF_8 {
+        out.extend_from_slice(UTF8_BOM);
+    }
+    let (bytes, _, unmappable) = encoding.encoding.encode(text);
+    if unmappable {
+        bail!(
+            "the text contains characters that can't be written as {}, use utf-8 instead",
+            encoding.name()
+        );
+    }
+    out.extend_from_slice(&bytes);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_encodings() {
+        for label in [
+            "utf-8",
+            "utf-8-bom",
+            "gbk",
+            "gb18030",
+            "utf-16le",
+            "utf-16be",
+        ] {
+            let encoding = TextEncoding::from_label(label).unwrap().unwrap();
+            let bytes = encode("订单,金额\r\n", encoding).unwrap();
+            assert_eq!(
+                decode(&bytes, Some(encoding)).unwrap(),
+                "订单,金额\r\n",
+                "{}",
+                label
+            );
+        }
+        assert!(TextEncoding::from_label("auto").unwrap().is_none());
+        assert!(TextEncoding::from_label("latin-9000").is_err());
+        assert!(encode("😀", TextEncoding::from_label("gbk").unwrap().unwrap()).is_err());
+    }
+
+    #[test]
+    fn detect_encodings() {
+        let gbk = TextEncoding::from_label("gbk").unwrap().unwrap();
+        assert_eq!(detect(&encode("订单编号", gbk).unwrap()), GB18030);
+        assert_eq!(detect("订单编号".as_bytes()), UTF_8);
+        assert_eq!(detect(b"\xEF\xBB\xBFid"), UTF_8);
+        assert_eq!(detect(b"i\0d\0,\0n\0"), UTF_16LE);
+        assert_eq!(decode(b"\xEF\xBB\xBFid", None).unwrap(), "id");
+    }
+}
@@ -0,0 +1,3 @@
+pub(crate) mod path;
+pub(crate) mod stat;
+pub(crate) mod walk;
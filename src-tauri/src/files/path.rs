@@ -0,0 +1,179 @@
+use std::path::{Component, Path, PathBuf, MAIN_SEPARATOR_STR};
+
+use anyhow::anyhow;
+
+fn to_string(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+/// remove `.` and resolve `..` without touching the filesystem, separators
+/// become the platform's, an empty path is `.`
+pub fn normalize(path: &str) -> String {
+    let mut out = PathBuf::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match out.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    out.pop();
+                }
+                // `..` of the root is the root
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => {}
+                // nothing left to go up from in a relative path
+                _ => out.push(".."),
+            },
+            c => out.push(c),
+        }
+    }
+    if out.as_os_str().is_empty() {
+        ".".to_string()
+    } else {
+        to_string(&out)
+    }
+}
+
+/// join the non-empty parts with the platform separator and normalize, an
+/// absolute part doesn't discard the ones before it
+pub fn join(parts: &[String]) -> String {
+    let parts: Vec<&str> = parts
+        .iter()
+        .map(|p| p.as_str())
+        .filter(|p| !p.is_empty())
+        .collect();
+    normalize(&parts.join(MAIN_SEPARATOR_STR))
+}
+
+/// absolute path of the parts applied in order to the working directory, an
+/// absolute part discards the ones before it
+pub fn resolve(parts: &[String], cwd: &Path) -> String {
+    let mut out = cwd.to_path_buf();
+    for part in parts.iter().filter(|p| !p.is_empty()) {
+        out.push(part);
+    }
+    normalize(&to_string(&out))
+}
+
+/// the folder of `path`, `.` for a bare file name
+pub fn dirname(path: &str) -> String {
+    match Path::new(path).parent() {
+        Some(parent) if parent.as_os_str().is_empty() => ".".to_string(),
+        Some(parent) => to_string(parent),
+        None => path.to_string(),
+    }
+}
+
+/// the last component of `path`
+pub fn basename(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// the extension of `path` with its dot, e.g. `.xlsx`, empty when there is none
+pub fn extname(path: &str) -> String {
+    Path::new(path)
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default()
+}
+
+/// the file name of `path` without its extension
+pub fn stem(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn same_component(a: &Component, b: &Component) -> bool {
+    if cfg!(windows) {
+        a.as_os_str().eq_ignore_ascii_case(b.as_os_str())
+    } else {
+        a == b
+    }
+}
+
+/// the path from `from` to `to`, both resolved against `cwd`, `to` itself
+/// when they are on different drives
+pub fn relative(from: &str, to: &str, cwd: &Path) -> String {
+    let from = resolve(&[from.to_string()], cwd);
+    let to = resolve(&[to.to_string()], cwd);
+    let from: Vec<Component> = Path::new(&from).components().collect();
+    let to: Vec<Component> = Path::new(&to).components().collect();
+    let common = from
+        .iter()
+        .zip(&to)
+        .take_while(|(a, b)| same_component(a, b))
+        .count();
+    if common == 0 {
+        return to_string(&to.iter().collect::<PathBuf>());
+    }
+    let mut out = PathBuf::new();
+    for _ in common..from.len() {
+        out.push("..");
+    }
+    for c in &to[common..] {
+        out.push(c);
+    }
+    to_string(&out)
+}
+
+/// a folder of the current user: `home`, `desktop`, `documents` or `downloads`
+pub fn user_dir(name: &str) -> anyhow::Result<String> {
+    let dir = match name {
+        "home" => dirs::home_dir(),
+        "desktop" => dirs::desktop_dir(),
+        "documents" => dirs::document_dir(),
+        "downloads" => dirs::download_dir(),
+        _ => return Err(anyhow!("unknown folder: {}", name)),
+    };
+    dir.map(|d| to_string(&d))
+        .ok_or_else(|| anyhow!("the {} folder of the current user was not found", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn same(actual: String, expected: &str) {
+        assert_eq!(Path::new(&actual), Path::new(expected), "{}", actual);
+    }
+
+    #[test]
+    fn normalize_paths() {
+        same(normalize("a/./b/../c/"), "a/c");
+        same(normalize("../a/../../b"), "../../b");
+        same(normalize("/../a"), "/a");
+        same(normalize(""), ".");
+        same(
+            join(&["a".into(), "".into(), "/b".into(), "../c.xlsx".into()]),
+            "a/c.xlsx",
+        );
+    }
+
+    #[test]
+    fn path_parts() {
+        assert_eq!(dirname("report.xlsx"), ".");
+        same(dirname("/data/2024/report.xlsx"), "/data/2024");
+        assert_eq!(basename("/data/report.xlsx"), "report.xlsx");
+        assert_eq!(extname("/data/report.tar.gz"), ".gz");
+        assert_eq!(extname("/data/.env"), "");
+        assert_eq!(stem("/data/report.xlsx"), "report");
+    }
+
+    #[test]
+    fn resolve_relative() {
+        let cwd = Path::new("/home/user");
+        same(
+            resolve(&["out".into(), "../a.csv".into()], cwd),
+            "/home/user/a.csv",
+        );
+        same(resolve(&["out".into(), "/tmp".into()], cwd), "/tmp");
+        same(
+            relative("/data/2024", "/data/2023/a.xlsx", cwd),
+            "../2023/a.xlsx",
+        );
+        same(relative("docs", "docs/a", cwd), "a");
+    }
+}
@@ -0,0 +1,69 @@
+use std::fs;
+
+use anyhow::Context;
+use serde::Serialize;
+
+use super::walk::{epoch_millis, EntryKind};
+
+/// Metadata of a file or folder, times are milliseconds since the unix epoch
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileStat {
+    pub path: String,
+    /// what the path points to, a symlink is only reported as such when its target is missing
+    #[serde(rename = "type")]
+    pub kind: EntryKind,
+    pub size: u64,
+    pub created: Option<f64>,
+    pub modified: Option<f64>,
+    pub accessed: Option<f64>,
+    pub readonly: bool,
+    pub symlink: bool,
+}
+
+/// metadata of `path`, following symlinks
+pub fn stat(path: &str) -> anyhow::Result<FileStat> {
+    let link = fs::symlink_metadata(path).with_context(|| format!("failed to stat {}", path))?;
+    // a broken symlink has no target to describe, fall back to the link itself
+    let metadata = fs::metadata(path).unwrap_or_else(|_| link.clone());
+    let kind = if metadata.is_dir() {
+        EntryKind::Dir
+    } else if metadata.is_symlink() {
+        EntryKind::Symlink
+    } else {
+        EntryKind::File
+    };
+    Ok(FileStat {
+        path: path.to_string(),
+        kind,
+        size: if metadata.is_file() {
+            metadata.len()
+        } else {
+            0
+        },
+        created: epoch_millis(metadata.created()),
+        modified: epoch_millis(metadata.modified()),
+        accessed: epoch_millis(metadata.accessed()),
+        readonly: metadata.permissions().readonly(),
+        symlink: link.is_symlink(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stat_file() {
+        let path = std::env::temp_dir().join(format!("xls-dsl-stat-{}.txt", std::process::id()));
+        fs::write(&path, "hello").unwrap();
+        let info = stat(&path.to_string_lossy()).unwrap();
+        assert_eq!(info.kind, EntryKind::File);
+        assert_eq!(info.size, 5);
+        assert!(info.modified.is_some());
+        assert!(!info.readonly);
+        assert!(!info.symlink);
+        fs::remove_file(&path).unwrap();
+        assert!(stat(&path.to_string_lossy()).is_err());
+    }
+}
@@ -0,0 +1,251 @@
+use std::{
+    path::{Component, Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+/// Options accepted by `walk`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WalkOptions {
+    /// glob patterns such as `*.xlsx` or `2024/**/*.csv` matched against the
+    /// path relative to the root, every entry is returned when empty
+    pub include: Vec<String>,
+    /// glob patterns of entries to skip, a matching folder is not entered
+    pub exclude: Vec<String>,
+    /// 1 only lists the root's children, unlimited when unset
+    pub max_depth: Option<usize>,
+    /// only return files, or only folders
+    pub kind: Option<EntryKind>,
+    /// walk into folders that symlinks point to
+    pub follow_links: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryKind {
+    File,
+    Dir,
+    Symlink,
+}
+
+/// A file or folder found by `walk`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileEntry {
+    pub path: String,
+    /// path relative to the root, always `/` separated
+    pub relative: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: EntryKind,
+    pub size: u64,
+    /// milliseconds since the unix epoch
+    pub modified: Option<f64>,
+}
+
+/// milliseconds since the unix epoch, negative before it, `None` when the
+/// platform doesn't record the time
+pub fn epoch_millis(time: std::io::Result<SystemTime>) -> Option<f64> {
+    let time = time.ok()?;
+    Some(match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs_f64() * 1000.0,
+        Err(e) => -e.duration().as_secs_f64() * 1000.0,
+    })
+}
+
+fn glob_set(patterns: &[String]) -> anyhow::Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = GlobBuilder::new(pattern.trim_start_matches("./"))
+            .case_insensitive(cfg!(windows))
+            .backslash_escape(false)
+            .build()
+            .with_context(|| format!("invalid glob pattern: {}", pattern))?;
+        builder.add(glob);
+    }
+    Ok(builder.build()?)
+}
+
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// list the files and folders under `root` sorted by name, entries that
+/// can't be read (e.g. without permission) are skipped
+pub fn walk(root: &str, options: &WalkOptions) -> anyhow::Result<Vec<FileEntry>> {
+    let root = Path::new(root);
+    if !root.is_dir() {
+        bail!("not a folder: {}", root.display());
+    }
+    let include = glob_set(&options.include)?;
+    let exclude = glob_set(&options.exclude)?;
+
+    let mut walker = WalkDir::new(root)
+        .min_depth(1)
+        .follow_links(options.follow_links)
+        .sort_by_file_name();
+    if let Some(depth) = options.max_depth {
+        walker = walker.max_depth(depth);
+    }
+
+    let mut entries = Vec::new();
+    let iter = walker
+        .into_iter()
+        .filter_entry(|e| !exclude.is_match(relative_path(root, e.path())));
+    for entry in iter.filter_map(Result::ok) {
+        let relative = relative_path(root, entry.path());
+        if !include.is_empty() && !include.is_match(&relative) {
+            continue;
+        }
+        let file_type = entry.file_type();
+        let kind = if file_type.is_symlink() {
+            EntryKind::Symlink
+        } else if file_type.is_dir() {
+            EntryKind::Dir
+        } else {
+            EntryKind::File
+        };
+        if options.kind.is_some_and(|k| k != kind) {
+            continue;
+        }
+        let metadata = entry.metadata().ok();
+        entries.push(FileEntry {
+            path: entry.path().to_string_lossy().to_string(),
+            relative,
+            name: entry.file_name().to_string_lossy().to_string(),
+            kind,
+            size: metadata
+                .as_ref()
+                .filter(|m| m.is_file())
+                .map_or(0, |m| m.len()),
+            modified: metadata.and_then(|m| epoch_millis(m.modified())),
+        });
+    }
+    Ok(entries)
+}
+
+/// split a pattern such as `C:/share/**/*.xlsx` into the folder to walk and
+/// the pattern relative to it
+pub fn split_glob(pattern: &str) -> (PathBuf, String) {
+    let is_glob = |s: &str| s.contains(['*', '?', '[', '{']);
+    let mut root = PathBuf::new();
+    let mut rest = Vec::new();
+    for component in Path::new(pattern).components() {
+        let part = component.as_os_str().to_string_lossy();
+        if rest.is_empty() && !is_glob(&part) {
+            root.push(component);
+        } else if !matches!(component, Component::CurDir) {
+            rest.push(part.to_string());
+        }
+    }
+    (root, rest.join("/"))
+}
+
+/// the files and folders matching a glob pattern, relative patterns start
+/// from `cwd`
+pub fn glob(pattern: &str, cwd: &str, options: &WalkOptions) -> anyhow::Result<Vec<FileEntry>> {
+    let (root, rest) = split_glob(pattern);
+    let root = Path::new(cwd).join(root);
+    if rest.is_empty() {
+        // no wildcards, the pattern names a single path
+        let parent = root.parent().unwrap_or(&root);
+        let name = root.file_name().map(|n| n.to_string_lossy().to_string());
+        return match name {
+            Some(name) if root.exists() => {
+                let options = WalkOptions {
+                    include: vec![globset::escape(&name)],
+                    max_depth: Some(1),
+                    ..options.clone()
+                };
+                walk(&parent.to_string_lossy(), &options)
+            }
+            _ => Ok(Vec::new()),
+        };
+    }
+    let options = WalkOptions {
+        max_depth: if rest.contains("**") {
+            options.max_depth
+        } else {
+            Some(rest.split('/').count())
+        },
+        include: vec![rest],
+        ..options.clone()
+    };
+    walk(&root.to_string_lossy(), &options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn split_glob_root() {
+        assert_eq!(
+            split_glob("share/2024/**/*.xlsx"),
+            (PathBuf::from("share/2024"), "**/*.xlsx".to_string())
+        );
+        assert_eq!(
+            split_glob("./*.csv"),
+            (PathBuf::from("."), "*.csv".to_string())
+        );
+        assert_eq!(
+            split_glob("a/b.txt"),
+            (PathBuf::from("a/b.txt"), String::new())
+        );
+    }
+
+    #[test]
+    fn walk_with_patterns() {
+        let root = std::env::temp_dir().join(format!("xls-dsl-walk-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("2024/q1")).unwrap();
+        fs::create_dir_all(root.join("tmp")).unwrap();
+        fs::write(root.join("a.xlsx"), "a").unwrap();
+        fs::write(root.join("2024/b.xlsx"), "bb").unwrap();
+        fs::write(root.join("2024/q1/c.xlsx"), "ccc").unwrap();
+        fs::write(root.join("2024/q1/c.csv"), "").unwrap();
+        fs::write(root.join("tmp/d.xlsx"), "").unwrap();
+        let root_str = root.to_string_lossy().to_string();
+
+        let options = WalkOptions {
+            include: vec!["*.xlsx".into()],
+            exclude: vec!["tmp".into()],
+            ..Default::default()
+        };
+        let entries = walk(&root_str, &options).unwrap();
+        let names: Vec<_> = entries.iter().map(|e| e.relative.as_str()).collect();
+        assert_eq!(names, ["2024/b.xlsx", "2024/q1/c.xlsx", "a.xlsx"]);
+        assert_eq!(entries[1].size, 3);
+        assert_eq!(entries[1].kind, EntryKind::File);
+        assert!(entries[1].modified.is_some());
+
+        let options = WalkOptions {
+            max_depth: Some(1),
+            kind: Some(EntryKind::Dir),
+            ..Default::default()
+        };
+        let names: Vec<_> = walk(&root_str, &options)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.name)
+            .collect();
+        assert_eq!(names, ["2024", "tmp"]);
+
+        let found = glob("*/*.xlsx", &root_str, &WalkOptions::default()).unwrap();
+        let names: Vec<_> = found.iter().map(|e| e.relative.as_str()).collect();
+        assert_eq!(names, ["2024/b.xlsx", "tmp/d.xlsx"]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
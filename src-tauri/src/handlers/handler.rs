@@ -3,7 +3,7 @@ use chrono::Local;
 
 use crate::dao::file_dao;
 use crate::dao::models::{NewFile, XlsFile};
-use crate::deno::lib::{emit_log, DenoRuntime};
+use crate::deno::lib::{self, emit_log, DenoRuntime};
 
 
 #[tauri::command]
@@ -32,8 +32,27 @@ pub(crate) fn update_code_by_id(id: i32, code: String) -> XlsFile {
 }
 
 #[tauri::command]
-pub(crate) fn update_name_xls_by_id(id: i32, name: String, xls: String) -> XlsFile {
-    file_dao::update_name_xls_by_id(id, name, xls).unwrap()
+pub(crate) fn update_name_xls_by_id(
+    id: i32,
+    name: String,
+    xls: String,
+    timeout_secs: Option<i32>,
+    max_heap_mb: Option<i32>,
+) -> XlsFile {
+    file_dao::update_name_xls_by_id(id, name, xls, timeout_secs, max_heap_mb).unwrap()
+}
+
+// 更新脚本的输出目录和允许访问的目录
+#[tauri::command]
+pub(crate) fn update_permissions_by_id(
+    id: i32,
+    output_dir: Option<String>,
+    read_dirs: Option<String>,
+    write_dirs: Option<String>,
+    prompt_access: Option<bool>,
+) -> XlsFile {
+    file_dao::update_permissions_by_id(id, output_dir, read_dirs, write_dirs, prompt_access)
+        .unwrap()
 }
 
 #[tauri::command]
@@ -46,19 +65,28 @@ pub(crate) fn get_by_id(id: i32) -> XlsFile {
     file_dao::get_by_id(id).unwrap()
 }
 
+// 运行脚本，返回运行id，可用于取消运行；试运行时不修改文件，只输出计划的变更
 #[tauri::command]
-pub(crate) fn run(id: i32) -> Result<String, String> {
+pub(crate) fn run(id: i32, dry_run: Option<bool>) -> Result<String, String> {
     let file: XlsFile = file_dao::get_by_id(id).expect("id not found");
+    let run_id = uuid::Uuid::new_v4().to_string();
+    lib::start_run(&run_id);
+    let result = run_id.clone();
 
     // 使用 std::thread 创建一个新线程来运行异步任务
     std::thread::spawn(move || {
         // 在新线程中运行异步任务
         actix_rt::System::new().block_on(async {
-            let res = DenoRuntime::new(file).run_script().await;
+            let res = DenoRuntime::new(file, run_id)
+                .dry_run(dry_run.unwrap_or(false))
+                .run_script()
+                .await;
             match res {
-                Ok(_) => {
+                Ok(None) => {
                     emit_log("println", RunLog::result("success".to_string()));
                 },
+                // 取消、超时和内存超限已由 run_script 报告
+                Ok(Some(_)) => {},
                 Err(e) => {
                     eprintln!("Error running script: {}", e);
                     emit_log("println", RunLog::error(format!("{:?}", e)));
@@ -67,5 +95,11 @@ pub(crate) fn run(id: i32) -> Result<String, String> {
         });
     });
 
-    Ok("success".to_string())
+    Ok(result)
+}
+
+// 取消正在运行的脚本，返回是否找到该运行
+#[tauri::command]
+pub(crate) fn cancel_run(run_id: String) -> bool {
+    lib::cancel_run(&run_id)
 }
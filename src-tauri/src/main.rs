@@ -6,6 +6,8 @@
 mod collections;
 mod dao;
 mod deno;
+mod encoding;
+mod files;
 mod handlers;
 mod parse_xls;
 
@@ -34,7 +36,9 @@ fn main() {
             handler::update_file,
             handler::get_by_id,
             handler::update_name_xls_by_id,
-            handler::run
+            handler::update_permissions_by_id,
+            handler::run,
+            handler::cancel_run
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
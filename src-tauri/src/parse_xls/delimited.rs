@@ -0,0 +1,387 @@
+use std::{fs, io::Write, path::Path};
+
+use anyhow::{anyhow, bail, Context};
+use calamine::{Data, Range, Sheet, SheetType, SheetVisible};
+use csv::{QuoteStyle, ReaderBuilder, Terminator, WriterBuilder};
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::lib::{parse_iso_date, sheet_data, slice_range, ReadOptions, SheetData};
+use super::reference::A1Range;
+use crate::encoding::lib::{decode, encode, TextEncoding};
+
+/// delimiters tried when the options don't name one
+const CANDIDATES: [char; 4] = [',', '\t', ';', '|'];
+/// number of records looked at to detect the delimiter
+const SAMPLE_RECORDS: usize = 20;
+
+/// Options accepted by `read_csv`, on top of the `ReadOptions` of a sheet
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CsvOptions {
+    /// single character field separator such as `,`, `;`, `|` or `\t`, detected when unset
+    pub delimiter: Option<String>,
+    /// quote character, `"` when unset
+    pub quote: Option<String>,
+    /// `utf-8`, `gbk`, `utf-16le`..., detected when unset or `auto`
+    pub encoding: Option<String>,
+    /// keep every field as text instead of reading numbers and `TRUE`/`FALSE`
+    pub raw: bool,
+    #[serde(flatten)]
+    pub read: ReadOptions,
+}
+
+fn single_byte(value: &str, what: &str) -> anyhow::Result<u8> {
+    match value.as_bytes() {
+        [b] if b.is_ascii() => Ok(*b),
+        _ => bail!("{} must be a single ascii character: {:?}", what, value),
+    }
+}
+
+/// occurrences of `delimiter` outside quotes in each of the first records of `text`
+fn delimiter_counts(text: &str, delimiter: char, quote: char) -> Vec<usize> {
+    let mut counts = Vec::new();
+    let mut count = 0;
+    let mut in_quotes = false;
+    let mut blank = true;
+    for c in text.chars() {
+        if c == quote {
+            in_quotes = !in_quotes;
+        } else if !in_quotes && c == '\n' {
+            if !blank {
+                counts.push(count);
+                if counts.len() == SAMPLE_RECORDS {
+                    return counts;
+                }
+            }
+            count = 0;
+            blank = true;
+            continue;
+        } else if !in_quotes && c == delimiter {
+            count += 1;
+        }
+        if !c.is_whitespace() {
+            blank = false;
+        }
+    }
+    if !blank {
+        counts.push(count);
+    }
+    counts
+}
+
+/// pick the delimiter that splits the first records into the same number of
+/// fields, preferring more fields, `.tsv` files are always tab separated
+fn detect_delimiter(text: &str, path: &str, quote: char) -> u8 {
+    let ext = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    if matches!(ext.as_deref(), Some("tsv") | Some("tab")) {
+        return b'\t';
+    }
+    let mut best = ((false, 0), ',');
+    for candidate in CANDIDATES {
+        let counts = delimiter_counts(text, candidate, quote);
+        let consistent = counts
+            .first()
+            .is_some_and(|first| *first > 0 && counts.iter().all(|c| c == first));
+        let score = if consistent {
+            (true, counts[0])
+        } else {
+            (false, counts.iter().sum())
+        };
+        if score > best.0 {
+            best = (score, candidate);
+        }
+    }
+    best.1 as u8
+}
+
+/// the cell a field becomes, numbers and `TRUE`/`FALSE` are typed the way
+/// excel types them when opening a csv, except that codes with leading zeros
+/// such as `007` and numbers too long for a double stay text
+fn field_value(field: &str, raw: bool) -> Data {
+    if field.is_empty() {
+        return Data::Empty;
+    }
+    if raw || field.trim() != field {
+        return Data::String(field.to_string());
+    }
+    if field.eq_ignore_ascii_case("true") {
+        return Data::Bool(true);
+    }
+    if field.eq_ignore_ascii_case("false") {
+        return Data::Bool(false);
+    }
+    let digits = field.trim_start_matches('-');
+    let numeric = digits
+        .chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-'))
+        && digits.starts_with(|c: char| c.is_ascii_digit())
+        && !(digits.len() > 1 && digits.starts_with('0') && !digits[1..].starts_with('.'))
+        && digits.chars().filter(char::is_ascii_digit).count() <= 15;
+    match field.parse::<f64>() {
+        Ok(n) if numeric && n.is_finite() => Data::Float(n),
+        _ => Data::String(field.to_string()),
+    }
+}
+
+/// parse csv `text` into a sheet named `name`, `path` is only used to detect the delimiter
+fn csv_sheet(
+    name: &str,
+    path: &str,
+    text: &str,
+    options: &CsvOptions,
+) -> anyhow::Result<SheetData> {
+    let quote = match &options.quote {
+        Some(q) => single_byte(q, "quote")?,
+        None => b'"',
+    };
+    let delimiter = match &options.delimiter {
+        Some(d) => single_byte(d, "delimiter")?,
+        None => detect_delimiter(text, path, quote as char),
+    };
+
+    let mut reader = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .quote(quote)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let mut rows: Vec<Vec<Data>> = Vec::new();
+    let mut width = 0;
+    for record in reader.records() {
+        let record = record.map_err(|e| anyhow!("failed to parse {}: {}", path, e))?;
+        let row: Vec<Data> = record.iter().map(|f| field_value(f, options.raw)).collect();
+        width = width.max(row.len());
+        rows.push(row);
+    }
+
+    let mut range = if rows.is_empty() || width == 0 {
+        Range::empty()
+    } else {
+        Range::new((0, 0), (rows.len() as u32 - 1, width as u32 - 1))
+    };
+    for (r, row) in rows.into_iter().enumerate() {
+        for (c, value) in row.into_iter().enumerate() {
+            if value != Data::Empty {
+                range.set_value((r as u32, c as u32), value);
+            }
+        }
+    }
+    if let Some(r) = options.read.range.as_deref() {
+        let r = A1Range::parse(r)?;
+        range = slice_range(&range, r.start, r.end);
+    }
+
+    let sheet = Sheet {
+        name: name.to_string(),
+        typ: SheetType::WorkSheet,
+        visible: SheetVisible::Visible,
+    };
+    Ok(sheet_data(
+        0,
+        &sheet,
+        &range,
+        None,
+        &[],
+        None,
+        &options.read,
+    ))
+}
+
+/// read a csv or tsv file as a single sheet named after the file, rows have
+/// the same shape as `ParseXls::read_sheet`
+pub fn read_csv(path: &str, options: &CsvOptions) -> anyhow::Result<SheetData> {
+    let bytes = std::fs::read(path).with_context(|| format!("failed to read {}", path))?;
+    let text = decode(&bytes, TextEncoding::parse(options.encoding.as_deref())?)
+        .with_context(|| format!("failed to read {}", path))?;
+    let name = Path::new(path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    csv_sheet(name, path, &text, options)
+}
+
+/// Options accepted by `csv_bytes`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CsvWriteOptions {
+    /// single character field separator, `,` when unset
+    pub delimiter: Option<String>,
+    /// quote character, `"` when unset
+    pub quote: Option<String>,
+    /// `crlf` (the default, what excel writes) or `lf`
+    pub line_ending: Option<String>,
+    /// `utf-8-bom` when unset so that excel doesn't read utf-8 as the ansi code page
+    pub encoding: Option<String>,
+    /// quote every field instead of only the ones that need it
+    pub quote_all: bool,
+    /// add the rows to the end of the file instead of replacing it
+    pub append: bool,
+}
+
+/// the text of a cell, dates as sent by the runtime (`{"$date": ...}`) are
+/// written the way excel shows them, other objects as json
+fn field_text(value: &Value) -> anyhow::Result<String> {
+    Ok(match value {
+        Value::Null => String::new(),
+        Value::Bool(b) => if *b { "TRUE" } else { "FALSE" }.to_string(),
+        // js numbers arrive as doubles, whole numbers are written without `.0`
+        Value::Number(n) => match n.as_f64() {
+            Some(f) if f.fract() == 0.0 && f.abs() < 1e15 => format!("{}", f as i64),
+            _ => n.to_string(),
+        },
+        Value::String(s) => s.clone(),
+        Value::Object(o) => match o.get("$date").and_then(Value::as_str) {
+            Some(date) if o.len() == 1 => {
+                let dt = parse_iso_date(date)?;
+                if dt.time() == chrono::NaiveTime::MIN {
+                    dt.format("%Y-%m-%d").to_string()
+                } else {
+                    dt.format("%Y-%m-%d %H:%M:%S").to_string()
+                }
+            }
+            _ => value.to_string(),
+        },
+        Value::Array(_) => value.to_string(),
+    })
+}
+
+/// format `rows` as csv text
+fn csv_text(rows: &[Vec<Value>], options: &CsvWriteOptions) -> anyhow::Result<String> {
+    let delimiter = match &options.delimiter {
+        Some(d) => single_byte(d, "delimiter")?,
+        None => b',',
+    };
+    let quote = match &options.quote {
+        Some(q) => single_byte(q, "quote")?,
+        None => b'"',
+    };
+    let terminator = match options.line_ending.as_deref().map(str::to_ascii_lowercase) {
+        None => Terminator::CRLF,
+        Some(ending) => match ending.as_str() {
+            "crlf" | "\r\n" | "windows" => Terminator::CRLF,
+            "lf" | "\n" | "unix" => Terminator::Any(b'\n'),
+            _ => bail!("lineEnding must be \"crlf\" or \"lf\": {:?}", ending),
+        },
+    };
+
+    let mut writer = WriterBuilder::new()
+        .delimiter(delimiter)
+        .quote(quote)
+        .terminator(terminator)
+        .quote_style(if options.quote_all {
+            QuoteStyle::Always
+        } else {
+            QuoteStyle::Necessary
+        })
+        .flexible(true)
+        .from_writer(Vec::new());
+    for row in rows {
+        let fields = row
+            .iter()
+            .map(field_text)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        writer.write_record(&fields)?;
+    }
+    let bytes = writer.into_inner().map_err(|e| anyhow!("{}", e.error()))?;
+    Ok(String::from_utf8(bytes)?)
+}
+
+/// the encoded bytes to write to the csv file at `path`, hand them to `write_csv`
+pub fn csv_bytes(
+    path: &str,
+    rows: &[Vec<Value>],
+    options: &CsvWriteOptions,
+) -> anyhow::Result<Vec<u8>> {
+    let mut encoding = TextEncoding::parse(options.encoding.as_deref())?.unwrap_or(TextEncoding {
+        bom: true,
+        ..TextEncoding::UTF8
+    });
+    let existing = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    if options.append && existing > 0 {
+        // the byte order mark only belongs at the start of the file
+        encoding.bom = false;
+    }
+    encode(&csv_text(rows, options)?, encoding)
+}
+
+/// write the bytes made by `csv_bytes` to a csv file, creating missing parent
+/// folders, `append` adds them to the end of an existing file
+pub fn write_csv(path: &str, bytes: &[u8], append: bool) -> anyhow::Result<()> {
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(path)
+        .with_context(|| format!("failed to write {}", path))?;
+    file.write_all(bytes)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn detect_csv_delimiter() {
+        assert_eq!(detect_delimiter("a;b;c\n1;2,5;3\n", "x.csv", '"'), b';');
+        assert_eq!(detect_delimiter("a\tb\n\"x,y\"\t2\n", "x.txt", '"'), b'\t');
+        assert_eq!(detect_delimiter("a,b\n1,2\n", "x.tsv", '"'), b'\t');
+        assert_eq!(detect_delimiter("single\n", "x.csv", '"'), b',');
+    }
+
+    #[test]
+    fn read_quoted_records() {
+        let text = "id,name,amount\n007,\"Li, \"\"Lei\"\"\nline 2\",12.50\n2,,TRUE\n";
+        let options = CsvOptions {
+            read: ReadOptions {
+                header: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let sheet = csv_sheet("orders", "orders.csv", text, &options).unwrap();
+        assert_eq!(
+            sheet.headers,
+            Some(vec!["id".into(), "name".into(), "amount".into()])
+        );
+        assert_eq!(
+            json!(sheet.rows),
+            json!([
+                {"id": "007", "name": "Li, \"Lei\"\nline 2", "amount": 12.5},
+                {"id": 2.0, "name": null, "amount": true}
+            ])
+        );
+    }
+
+    #[test]
+    fn write_quoted_fields() {
+        let rows = vec![
+            vec![json!("id"), json!("name"), json!("date")],
+            vec![
+                json!(7.0),
+                json!("Li, \"Lei\"\nline 2"),
+                json!({"$date": "2024-03-01T00:00:00.000"}),
+            ],
+            vec![json!(1.5), Value::Null, json!(true)],
+        ];
+        assert_eq!(
+            csv_text(&rows, &CsvWriteOptions::default()).unwrap(),
+            "id,name,date\r\n7,\"Li, \"\"Lei\"\"\nline 2\",2024-03-01\r\n1.5,,TRUE\r\n"
+        );
+        let options = CsvWriteOptions {
+            delimiter: Some(";".into()),
+            line_ending: Some("lf".into()),
+            ..Default::default()
+        };
+        assert_eq!(csv_text(&rows[2..], &options).unwrap(), "1.5;;TRUE\n");
+    }
+}
@@ -1,28 +1,781 @@
-use calamine::{open_workbook, Reader, Xlsx};
-use lazy_static::lazy_static;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context};
+use calamine::{
+    open_workbook, Cell, CellType, Data, Dimensions, Ods, Range, Reader, Sheet, SheetType,
+    SheetVisible, Sheets, Xls, Xlsb, Xlsx,
+};
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Map, Number, Value};
 
-lazy_static! {
-    static ref COLUMN_INDEX: Vec<String> = column_index();
-}
+use super::reference::{column_name, range_name, A1Range};
 
 pub type JsonObject = Map<String, Value>;
 
-fn column_index() -> Vec<String> {
-    let chars: Vec<String> = (b'A'..=b'Z')
-        .map(|x| (x as char).to_string())
-        .collect::<Vec<String>>();
-    let mut chars_vec = vec![];
-    for i in 0..702 {
-        if i >= 26 {
-            let two_len = i % 26;
-            let one_len = (i / 26) - 1;
-            chars_vec.push(format!("{}{}", chars[one_len], chars[two_len]));
+/// OLE2 compound document header, used by legacy `.xls` workbooks
+const OLE_MAGIC: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+/// zip local file header, used by `.xlsx`, `.xlsm`, `.xlsb` and `.ods`
+const ZIP_MAGIC: [u8; 4] = [0x50, 0x4B, 0x03, 0x04];
+/// an ods package stores its uncompressed `mimetype` entry first
+const ODS_MIMETYPE: &[u8] = b"application/vnd.oasis.opendocument.spreadsheet";
+
+/// Workbook formats understood by `ParseXls`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XlsFormat {
+    Xls,
+    Xlsx,
+    Xlsb,
+    Ods,
+}
+
+impl XlsFormat {
+    /// guess the format from the file extension
+    pub fn from_extension(path: &str) -> Option<Self> {
+        let ext = Path::new(path).extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "xls" | "xla" => Some(XlsFormat::Xls),
+            "xlsx" | "xlsm" | "xltx" | "xltm" | "xlam" => Some(XlsFormat::Xlsx),
+            "xlsb" => Some(XlsFormat::Xlsb),
+            "ods" => Some(XlsFormat::Ods),
+            _ => None,
+        }
+    }
+
+    /// detect the format from the leading bytes of the file, `hint` is the
+    /// format guessed from the extension and is used to tell zip based formats apart
+    pub fn from_magic(header: &[u8], hint: Option<XlsFormat>) -> Option<Self> {
+        if header.starts_with(&OLE_MAGIC) {
+            return Some(XlsFormat::Xls);
+        }
+        if header.starts_with(&ZIP_MAGIC) {
+            if header
+                .windows(ODS_MIMETYPE.len())
+                .any(|w| w == ODS_MIMETYPE)
+            {
+                return Some(XlsFormat::Ods);
+            }
+            return match hint {
+                Some(XlsFormat::Xlsb) => Some(XlsFormat::Xlsb),
+                _ => Some(XlsFormat::Xlsx),
+            };
+        }
+        None
+    }
+
+    /// detect the format of the workbook at `path`, the file content wins over
+    /// the extension so that a renamed file is still opened with the right reader
+    pub fn detect(path: &str) -> anyhow::Result<Self> {
+        let hint = XlsFormat::from_extension(path);
+        let mut header = Vec::with_capacity(128);
+        File::open(path)
+            .with_context(|| format!("failed to open workbook: {}", path))?
+            .take(128)
+            .read_to_end(&mut header)?;
+
+        match XlsFormat::from_magic(&header, hint) {
+            Some(format) => Ok(format),
+            None => bail!(
+                "unsupported workbook format: {} (expected xls, xlsx, xlsm, xlsb or ods; csv or html files saved with an excel extension are not supported)",
+                path
+            ),
+        }
+    }
+}
+
+/// open a workbook with the reader matching its detected format
+pub fn open_workbook_any(path: &str) -> anyhow::Result<Sheets<BufReader<File>>> {
+    let format = XlsFormat::detect(path)?;
+    let sheets = match format {
+        XlsFormat::Xls => {
+            Sheets::Xls(open_workbook::<Xls<_>, _>(path).map_err(|e| anyhow!("{}", e))?)
+        }
+        XlsFormat::Xlsx => {
+            Sheets::Xlsx(open_workbook::<Xlsx<_>, _>(path).map_err(|e| anyhow!("{}", e))?)
+        }
+        XlsFormat::Xlsb => {
+            Sheets::Xlsb(open_workbook::<Xlsb<_>, _>(path).map_err(|e| anyhow!("{}", e))?)
+        }
+        XlsFormat::Ods => {
+            Sheets::Ods(open_workbook::<Ods<_>, _>(path).map_err(|e| anyhow!("{}", e))?)
+        }
+    };
+    Ok(sheets)
+}
+
+/// Sheet to read, either its zero based index or its name
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum SheetSelector {
+    Index(usize),
+    Name(String),
+}
+
+impl fmt::Display for SheetSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SheetSelector::Index(i) => write!(f, "#{}", i),
+            SheetSelector::Name(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Options accepted by `ParseXls::read_sheet` and `ParseXls::read_sheets`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ReadOptions {
+    /// sheet to read, defaults to the sheet named in `range` or the first sheet
+    pub sheet: Option<SheetSelector>,
+    /// A1 style range such as `B2:F500` or `Sheet1!B2:F500`
+    pub range: Option<String>,
+    /// number of rows to drop from the top, e.g. title banners above the header
+    pub skip_rows: usize,
+    /// use the first row after `skip_rows` as header and key row objects by its text
+    pub header: bool,
+    /// how date, time and duration cells are returned
+    pub dates: DateMode,
+    /// how `#DIV/0!` style error cells are returned
+    pub errors: ErrorMode,
+    /// also return the formula text of computed cells in `SheetData::formulas`
+    pub formulas: bool,
+    /// report merged regions in `SheetData::merged`
+    pub merged_cells: bool,
+    /// copy the value of each merged region into every cell it covers
+    pub fill_merged: bool,
+}
+
+/// Representation of date, time and duration cells
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DateMode {
+    /// ISO-8601 strings, `2024-03-01T08:30:00` and `PT1H30M`
+    #[default]
+    Iso,
+    /// `{"$date": "2024-03-01T08:30:00"}`, revived to a JS `Date` by the runtime
+    Date,
+    /// the raw excel serial number
+    Serial,
+}
+
+/// Representation of cell errors
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ErrorMode {
+    /// `{"error": "#DIV/0!"}`
+    #[default]
+    Object,
+    /// the error text, `#DIV/0!`
+    String,
+    /// `null`, like empty cells
+    Null,
+}
+
+/// Used area of a sheet, rows and columns are zero based
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SheetDimensions {
+    pub start: Option<(u32, u32)>,
+    pub end: Option<(u32, u32)>,
+    pub height: usize,
+    pub width: usize,
+}
+
+impl SheetDimensions {
+    fn of<T: calamine::CellType>(range: &Range<T>) -> Self {
+        SheetDimensions {
+            start: range.start(),
+            end: range.end(),
+            height: range.height(),
+            width: range.width(),
+        }
+    }
+}
+
+/// A sheet together with its metadata, as returned to scripts
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SheetData {
+    pub name: String,
+    pub index: usize,
+    pub visible: &'static str,
+    pub sheet_type: &'static str,
+    pub dimensions: SheetDimensions,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub headers: Option<Vec<String>>,
+    pub rows: Vec<JsonObject>,
+    /// aligned with `rows`, each object only holds the keys of cells with a formula
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formulas: Option<Vec<JsonObject>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub merged: Option<Vec<MergedRegion>>,
+}
+
+/// A merged cell region, rows and columns are zero based
+#[derive(Debug, Clone, Serialize)]
+pub struct MergedRegion {
+    /// A1 style reference, e.g. `A1:C2`
+    pub range: String,
+    pub start: (u32, u32),
+    pub end: (u32, u32),
+}
+
+impl From<&Dimensions> for MergedRegion {
+    fn from(d: &Dimensions) -> Self {
+        MergedRegion {
+            range: range_name(d.start, d.end),
+            start: d.start,
+            end: d.end,
+        }
+    }
+}
+
+/// A workbook level defined name, `refers_to` is the formula as stored in
+/// the workbook, e.g. `Sheet1!$A$1:$C$10`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DefinedName {
+    pub name: String,
+    pub refers_to: String,
+}
+
+/// An excel table (ListObject), `range` covers the data rows without the
+/// header and is `None` for a table without data
+#[derive(Debug, Clone, Serialize)]
+pub struct TableInfo {
+    pub name: String,
+    pub sheet: String,
+    pub columns: Vec<String>,
+    pub range: Option<String>,
+    pub start: Option<(u32, u32)>,
+    pub end: Option<(u32, u32)>,
+}
+
+fn visible_name(visible: SheetVisible) -> &'static str {
+    match visible {
+        SheetVisible::Visible => "visible",
+        SheetVisible::Hidden => "hidden",
+        SheetVisible::VeryHidden => "veryHidden",
+    }
+}
+
+fn sheet_type_name(typ: SheetType) -> &'static str {
+    match typ {
+        SheetType::WorkSheet => "worksheet",
+        SheetType::DialogSheet => "dialogsheet",
+        SheetType::MacroSheet => "macrosheet",
+        SheetType::ChartSheet => "chartsheet",
+        SheetType::Vba => "vba",
+    }
+}
+
+/// format a duration as ISO-8601, e.g. `PT1H30M` or `-PT0.5S`
+fn iso_duration(d: chrono::Duration) -> String {
+    let sign = if d < chrono::Duration::zero() {
+        "-"
+    } else {
+        ""
+    };
+    let ms = d.num_milliseconds().abs();
+    let (h, m, s, ms) = (ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60, ms % 1000);
+    let mut out = format!("{}PT", sign);
+    if h > 0 {
+        out.push_str(&format!("{}H", h));
+    }
+    if m > 0 {
+        out.push_str(&format!("{}M", m));
+    }
+    if ms > 0 {
+        let frac = format!("{:03}", ms);
+        out.push_str(&format!("{}.{}S", s, frac.trim_end_matches('0')));
+    } else if s > 0 || (h == 0 && m == 0) {
+        out.push_str(&format!("{}S", s));
+    }
+    out
+}
+
+/// parse a date sent by scripts, `2024-03-01` or `2024-03-01T08:30:00` with
+/// optional fraction, the form `cell_to_json` returns
+pub fn parse_iso_date(s: &str) -> anyhow::Result<chrono::NaiveDateTime> {
+    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f") {
+        return Ok(dt);
+    }
+    if let Ok(d) = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Ok(d.and_time(chrono::NaiveTime::MIN));
+    }
+    bail!(
+        "invalid date: {}, expected 2024-03-01 or 2024-03-01T08:30:00",
+        s
+    )
+}
+
+/// excel serial number of a date time, days since 1899-12-30
+pub fn excel_serial(dt: chrono::NaiveDateTime) -> f64 {
+    let epoch = chrono::NaiveDate::from_ymd_opt(1899, 12, 30)
+        .unwrap_or_default()
+        .and_time(chrono::NaiveTime::MIN);
+    (dt - epoch).num_milliseconds() as f64 / 86_400_000.0
+}
+
+fn date_value(iso: String, options: &ReadOptions) -> Value {
+    match options.dates {
+        DateMode::Date => json!({ "$date": iso }),
+        _ => Value::String(iso),
+    }
+}
+
+/// convert a cell to its json representation
+fn cell_to_json(ele: &Data, options: &ReadOptions) -> Value {
+    match ele {
+        Data::Bool(b) => Value::Bool(*b),
+        Data::Float(f) => Number::from_f64(*f)
+            .map(Value::Number)
+            .unwrap_or(Value::Null),
+        Data::Int(i) => Value::Number(Number::from(*i)),
+        Data::String(s) => Value::String(s.clone()),
+        Data::DateTime(dt) if options.dates == DateMode::Serial => Number::from_f64(dt.as_f64())
+            .map(Value::Number)
+            .unwrap_or(Value::Null),
+        Data::DateTime(dt) if dt.is_duration() => match dt.as_duration() {
+            Some(d) => Value::String(iso_duration(d)),
+            None => Value::Null,
+        },
+        Data::DateTime(dt) => match dt.as_datetime() {
+            Some(d) => date_value(d.format("%Y-%m-%dT%H:%M:%S%.f").to_string(), options),
+            None => Value::Null,
+        },
+        Data::DateTimeIso(s) => date_value(s.clone(), options),
+        Data::DurationIso(s) => Value::String(s.clone()),
+        Data::Error(e) => match options.errors {
+            ErrorMode::Object => json!({ "error": e.to_string() }),
+            ErrorMode::String => Value::String(e.to_string()),
+            ErrorMode::Null => Value::Null,
+        },
+        Data::Empty => Value::Null,
+    }
+}
+
+/// column letters used as row object keys, `first_col` is the zero based
+/// column of the first cell in each row
+fn column_keys(first_col: usize, width: usize) -> Vec<String> {
+    (first_col..first_col + width)
+        .map(|c| column_name(c as u32))
+        .collect()
+}
+
+/// convert a row to an object keyed by `keys`
+fn row_to_json(row: &[Data], keys: &[String], options: &ReadOptions) -> JsonObject {
+    keys.iter()
+        .zip(row)
+        .map(|(key, cell)| (key.clone(), cell_to_json(cell, options)))
+        .collect()
+}
+
+/// build unique row object keys from a header row, blank headers fall back
+/// to the column letter and repeated headers get a `_2`, `_3`... suffix
+fn header_keys(row: &[Data], first_col: usize) -> Vec<String> {
+    let mut used: HashSet<String> = HashSet::new();
+    row.iter()
+        .enumerate()
+        .map(|(i, cell)| {
+            let text = cell.to_string().trim().to_string();
+            let base = if text.is_empty() {
+                column_name((first_col + i) as u32)
+            } else {
+                text
+            };
+            let mut key = base.clone();
+            let mut n = 1;
+            while used.contains(&key) {
+                n += 1;
+                key = format!("{}_{}", base, n);
+            }
+            used.insert(key.clone());
+            key
+        })
+        .collect()
+}
+
+/// Turns rows of cells into row objects, dropping `skip_rows` rows first and
+/// taking the keys from the next row when `options.header` is set
+pub(crate) struct RowShaper<'a> {
+    options: &'a ReadOptions,
+    first_col: usize,
+    skipped: usize,
+    keys: Option<Vec<String>>,
+    fixed: bool,
+}
+
+impl<'a> RowShaper<'a> {
+    /// `first_col` is the zero based column of the first cell in each row
+    pub fn new(options: &'a ReadOptions, first_col: usize) -> Self {
+        RowShaper {
+            options,
+            first_col,
+            skipped: 0,
+            keys: None,
+            fixed: false,
+        }
+    }
+
+    /// use `keys` for every row instead of reading a header row, e.g. the
+    /// column names of an excel table
+    pub fn with_keys(options: &'a ReadOptions, keys: Vec<String>) -> Self {
+        RowShaper {
+            options,
+            first_col: 0,
+            skipped: 0,
+            keys: Some(keys),
+            fixed: true,
+        }
+    }
+
+    /// the row object for `row`, or `None` for skipped and header rows
+    pub fn shape(&mut self, row: &[Data]) -> Option<JsonObject> {
+        if self.skipped < self.options.skip_rows {
+            self.skipped += 1;
+            return None;
+        }
+        if self.fixed {
+            // keys were given up front, there is no header row to consume
+        } else if self.options.header {
+            if self.keys.is_none() {
+                self.keys = Some(header_keys(row, self.first_col));
+                return None;
+            }
         } else {
-            chars_vec.push(format!("{}", chars[i]));
+            let short = match &self.keys {
+                Some(keys) => keys.len() < row.len(),
+                None => true,
+            };
+            if short {
+                self.keys = Some(column_keys(self.first_col, row.len()));
+            }
         }
+        let keys = self.keys.as_ref()?;
+        Some(row_to_json(row, keys, self.options))
     }
-    chars_vec
+
+    /// keys of the row objects, the header text in header mode
+    pub fn keys(&self) -> Option<&Vec<String>> {
+        self.keys.as_ref()
+    }
+
+    /// whether the row objects are keyed by header text rather than column letters
+    pub fn has_header(&self) -> bool {
+        self.fixed || self.options.header
+    }
+}
+
+/// merged regions of a sheet, the xlsb and ods readers don't expose them
+fn merged_regions(
+    workbook: &mut Sheets<BufReader<File>>,
+    name: &str,
+) -> anyhow::Result<Vec<Dimensions>> {
+    match workbook {
+        Sheets::Xlsx(xlsx) => match xlsx.worksheet_merge_cells(name) {
+            Some(regions) => {
+                regions.map_err(|e| anyhow!("failed to read merged cells of {}: {}", name, e))
+            }
+            None => Ok(vec![]),
+        },
+        Sheets::Xls(xls) => Ok(xls.worksheet_merge_cells(name).unwrap_or_default()),
+        Sheets::Xlsb(_) | Sheets::Ods(_) => Ok(vec![]),
+    }
+}
+
+/// copy the top-left value of every merged region into the other cells it covers
+fn fill_merged(range: &mut Range<Data>, regions: &[Dimensions]) {
+    let (Some(used_start), Some(used_end)) = (range.start(), range.end()) else {
+        return;
+    };
+    for region in regions {
+        let Some(value) = range.get_value(region.start).cloned() else {
+            continue;
+        };
+        for row in region.start.0.max(used_start.0)..=region.end.0.min(used_end.0) {
+            for col in region.start.1.max(used_start.1)..=region.end.1.min(used_end.1) {
+                range.set_value((row, col), value.clone());
+            }
+        }
+    }
+}
+
+/// formula text as typed in excel, with the leading `=`
+fn formula_text(formula: &str) -> String {
+    if formula.starts_with('=') {
+        formula.to_string()
+    } else {
+        format!("={}", formula)
+    }
+}
+
+/// convert a sheet range to `SheetData`, applying the row options, `formulas`
+/// is the formula range of the same sheet when `options.formulas` is set
+/// and `merged` its merged regions when `options.merged_cells` is set,
+/// `columns` replaces the header row, e.g. with the column names of a table
+pub(crate) fn sheet_data(
+    index: usize,
+    sheet: &Sheet,
+    range: &Range<Data>,
+    formulas: Option<&Range<String>>,
+    merged: &[Dimensions],
+    columns: Option<Vec<String>>,
+    options: &ReadOptions,
+) -> SheetData {
+    let first_col = range.start().map(|(_, c)| c as usize).unwrap_or(0);
+    let header_row = options.header && columns.is_none();
+    let mut shaper = match columns {
+        Some(columns) => RowShaper::with_keys(options, columns),
+        None => RowShaper::new(options, first_col),
+    };
+    let rows: Vec<JsonObject> = range.rows().filter_map(|row| shaper.shape(row)).collect();
+    let keys = shaper.keys().cloned().unwrap_or_default();
+    let headers = shaper.has_header().then(|| keys.clone());
+
+    // formulas are looked up by absolute position as their range can differ from the values
+    let first_row = range.start().map(|(r, _)| r).unwrap_or(0)
+        + options.skip_rows as u32
+        + u32::from(header_row);
+    let formulas = formulas.map(|f| {
+        (0..rows.len())
+            .map(|i| {
+                keys.iter()
+                    .enumerate()
+                    .filter_map(|(j, key)| {
+                        let pos = (first_row + i as u32, (first_col + j) as u32);
+                        match f.get_value(pos) {
+                            Some(text) if !text.is_empty() => {
+                                Some((key.clone(), Value::String(formula_text(text))))
+                            }
+                            _ => None,
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    });
+
+    SheetData {
+        name: sheet.name.clone(),
+        index,
+        visible: visible_name(sheet.visible),
+        sheet_type: sheet_type_name(sheet.typ),
+        dimensions: SheetDimensions::of(range),
+        headers,
+        rows,
+        formulas,
+        merged: options
+            .merged_cells
+            .then(|| merged.iter().map(MergedRegion::from).collect()),
+    }
+}
+
+/// cut `start..=end` out of `range`, clipped to the used area so that
+/// open ended references like `A:C` don't allocate a million rows
+pub(crate) fn slice_range<T: calamine::CellType>(
+    range: &Range<T>,
+    start: (u32, u32),
+    end: (u32, u32),
+) -> Range<T> {
+    match (range.start(), range.end()) {
+        (Some(used_start), Some(used_end)) => {
+            let start = (start.0.max(used_start.0), start.1.max(used_start.1));
+            let end = (end.0.min(used_end.0), end.1.min(used_end.1));
+            if start.0 > end.0 || start.1 > end.1 {
+                Range::empty()
+            } else {
+                range.range(start, end)
+            }
+        }
+        _ => Range::empty(),
+    }
+}
+
+/// index of the sheet selected by `options.sheet`, or by the sheet named in
+/// `range`, defaulting to the first sheet
+pub(crate) fn select_sheet(
+    metadata: &[Sheet],
+    options: &ReadOptions,
+    range_ref: Option<&A1Range>,
+) -> anyhow::Result<usize> {
+    let selector = match (&options.sheet, range_ref.and_then(|r| r.sheet.clone())) {
+        (Some(sheet), _) => sheet.clone(),
+        (None, Some(name)) => SheetSelector::Name(name),
+        (None, None) => SheetSelector::Index(0),
+    };
+    let index = match &selector {
+        SheetSelector::Index(i) => Some(*i).filter(|i| *i < metadata.len()),
+        SheetSelector::Name(name) => metadata.iter().position(|s| &s.name == name),
+    };
+    let Some(index) = index else {
+        bail!(
+            "sheet not found: {}, available sheets: {}",
+            selector,
+            metadata
+                .iter()
+                .map(|s| s.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    };
+    if let (Some(range_sheet), Some(SheetSelector::Name(name))) = (
+        range_ref.and_then(|r| r.sheet.as_ref()),
+        options.sheet.as_ref(),
+    ) {
+        if range_sheet != name {
+            bail!("range sheet {} does not match sheet {}", range_sheet, name);
+        }
+    }
+    Ok(index)
+}
+
+/// the cells within `start..=end` of a sheet read in row order from `next`,
+/// reading stops after the last requested row. The range covers the used
+/// area inside the bounds, `dims` is the sheet's declared area so leading
+/// empty rows and columns are kept like with a whole sheet read.
+fn bounded_range<T: CellType>(
+    dims: Dimensions,
+    start: (u32, u32),
+    end: (u32, u32),
+    mut next: impl FnMut() -> anyhow::Result<Option<Cell<T>>>,
+) -> anyhow::Result<Range<T>> {
+    let mut cells = Vec::new();
+    let mut used_start = dims.start;
+    let mut used_end = None;
+    while let Some(cell) = next()? {
+        let (r, c) = cell.get_position();
+        if r > end.0 {
+            break;
+        }
+        if r < start.0 || c < start.1 || c > end.1 {
+            continue;
+        }
+        used_start = (used_start.0.min(r), used_start.1.min(c));
+        used_end = Some(used_end.map_or((r, c), |(er, ec): (u32, u32)| (er.max(r), ec.max(c))));
+        cells.push(cell);
+    }
+    let Some(used_end) = used_end else {
+        return Ok(Range::empty());
+    };
+    let start = (start.0.max(used_start.0), start.1.max(used_start.1));
+    let mut range = Range::new(start, used_end);
+    for cell in cells {
+        range.set_value(cell.get_position(), cell.get_value().clone());
+    }
+    Ok(range)
+}
+
+/// values of a sheet, for xlsx limited to `bounds` without parsing the rows
+/// below them, the xls, xlsb and ods readers can only load a whole sheet
+fn sheet_values(
+    workbook: &mut Sheets<BufReader<File>>,
+    name: &str,
+    bounds: Option<((u32, u32), (u32, u32))>,
+) -> anyhow::Result<Range<Data>> {
+    if let (Sheets::Xlsx(xlsx), Some((start, end))) = (&mut *workbook, bounds) {
+        let mut cells = xlsx
+            .worksheet_cells_reader(name)
+            .map_err(|e| anyhow!("failed to read sheet {}: {}", name, e))?;
+        let dims = cells.dimensions();
+        return bounded_range(dims, start, end, || {
+            let cell = cells
+                .next_cell()
+                .map_err(|e| anyhow!("failed to read sheet {}: {}", name, e))?;
+            Ok(cell.map(|c| Cell::new(c.get_position(), Data::from(c.get_value().clone()))))
+        });
+    }
+    workbook
+        .worksheet_range(name)
+        .map_err(|e| anyhow!("failed to read sheet {}: {}", name, e))
+}
+
+/// formulas of a sheet, limited like `sheet_values`
+fn sheet_formulas(
+    workbook: &mut Sheets<BufReader<File>>,
+    name: &str,
+    bounds: Option<((u32, u32), (u32, u32))>,
+) -> anyhow::Result<Range<String>> {
+    if let (Sheets::Xlsx(xlsx), Some((start, end))) = (&mut *workbook, bounds) {
+        let mut cells = xlsx
+            .worksheet_cells_reader(name)
+            .map_err(|e| anyhow!("failed to read formulas of {}: {}", name, e))?;
+        let dims = cells.dimensions();
+        return bounded_range(dims, start, end, || loop {
+            let cell = cells
+                .next_formula()
+                .map_err(|e| anyhow!("failed to read formulas of {}: {}", name, e))?;
+            match cell {
+                // cells without a formula come back with empty text
+                Some(c) if c.get_value().is_empty() => continue,
+                cell => return Ok(cell),
+            }
+        });
+    }
+    workbook
+        .worksheet_formula(name)
+        .map_err(|e| anyhow!("failed to read formulas of {}: {}", name, e))
+}
+
+/// read sheet `index`, limited to `bounds` when given, see `sheet_data`
+fn read_range(
+    workbook: &mut Sheets<BufReader<File>>,
+    index: usize,
+    sheet: &Sheet,
+    bounds: Option<((u32, u32), (u32, u32))>,
+    columns: Option<Vec<String>>,
+    options: &ReadOptions,
+) -> anyhow::Result<SheetData> {
+    let mut merged = if options.merged_cells || options.fill_merged {
+        merged_regions(workbook, &sheet.name)?
+    } else {
+        vec![]
+    };
+    if let Some((start, end)) = bounds {
+        merged.retain(|m| {
+            m.start.0 <= end.0 && m.end.0 >= start.0 && m.start.1 <= end.1 && m.end.1 >= start.1
+        });
+    }
+    // regions starting above or left of the range need their top-left value
+    let load = bounds.map(|(start, end)| {
+        let start = merged
+            .iter()
+            .filter(|_| options.fill_merged)
+            .fold(start, |s, m| (s.0.min(m.start.0), s.1.min(m.start.1)));
+        (start, end)
+    });
+    let mut range = sheet_values(workbook, &sheet.name, load)?;
+    // fill before slicing so regions starting above the range still propagate
+    if options.fill_merged {
+        fill_merged(&mut range, &merged);
+    }
+    if let Some((start, end)) = bounds {
+        range = slice_range(&range, start, end);
+    }
+
+    let formulas = if options.formulas {
+        Some(sheet_formulas(workbook, &sheet.name, bounds)?)
+    } else {
+        None
+    };
+    Ok(sheet_data(
+        index,
+        sheet,
+        &range,
+        formulas.as_ref(),
+        &merged,
+        columns,
+        options,
+    ))
+}
+
+/// absolute bounds of a table's data rows, `None` when the table has no
+/// data, excel keeps a single blank data row in an empty table
+fn table_bounds<T: calamine::CellType>(data: &Range<T>) -> Option<((u32, u32), (u32, u32))> {
+    data.used_cells().next()?;
+    Some((data.start()?, data.end()?))
 }
 
 pub(crate) struct ParseXls {
@@ -30,41 +783,349 @@ pub(crate) struct ParseXls {
 }
 
 impl ParseXls {
+    fn open(&self) -> anyhow::Result<Sheets<BufReader<File>>> {
+        open_workbook_any(self.xls_path.as_str())
+            .with_context(|| format!("failed to read workbook: {}", self.xls_path))
+    }
+
     /// read xls function
     pub fn read_all(&mut self) -> anyhow::Result<Value> {
-        let mut workbook: Xlsx<_> = open_workbook(self.xls_path.as_str())?;
+        let mut workbook = self.open()?;
+        let options = ReadOptions::default();
         let mut sheets = Vec::new();
         workbook.worksheets().iter().for_each(|(_, sheet_data)| {
-            let mut row_data_ve: Vec<JsonObject> = vec![];
-            sheet_data.rows().for_each(|x| {
-                let mut row_data: JsonObject = JsonObject::new();
-                for i in 0..x.len() {
-                    let ele = &x[i];
-                    let index_s = COLUMN_INDEX.get(i).unwrap();
-                    if ele.is_bool() {
-                        row_data.insert(index_s.clone(), Value::Bool(ele.get_bool().unwrap()));
-                    } else if ele.is_float() {
-                        let data: f64 = ele.get_float().unwrap();
-                        let dataview = Number::from_f64(data);
-                        row_data.insert(index_s.clone(), Value::Number(dataview.unwrap()));
-                    } else if ele.is_int() {
-                        row_data.insert(
-                            index_s.clone(),
-                            Value::Number(Number::from(ele.get_int().unwrap())),
-                        );
-                    } else if ele.is_string() {
-                        row_data.insert(
-                            index_s.clone(),
-                            Value::String(ele.get_string().unwrap().to_string()),
-                        );
-                    } else {
-                        row_data.insert(index_s.clone(), Value::Null);
-                    }
-                }
-                row_data_ve.push(row_data)
-            });
+            let keys = column_keys(0, sheet_data.width());
+            let row_data_ve: Vec<JsonObject> = sheet_data
+                .rows()
+                .map(|x| row_to_json(x, &keys, &options))
+                .collect();
             sheets.push(row_data_ve);
         });
         Ok(json!(sheets))
     }
+
+    /// read a single sheet, optionally limited to a cell range, without
+    /// deserialising the other sheets of the workbook, an xlsx sheet is only
+    /// parsed down to the last row of the range
+    pub fn read_sheet(&mut self, options: &ReadOptions) -> anyhow::Result<SheetData> {
+        let mut workbook = self.open()?;
+        let range_ref = options.range.as_deref().map(A1Range::parse).transpose()?;
+
+        let metadata = workbook.sheets_metadata().to_vec();
+        let index = select_sheet(&metadata, options, range_ref.as_ref())?;
+        read_range(
+            &mut workbook,
+            index,
+            &metadata[index],
+            range_ref.map(|r| (r.start, r.end)),
+            None,
+            options,
+        )
+    }
+
+    /// defined names of the workbook, including hidden built-in names such
+    /// as `_xlnm._FilterDatabase`
+    pub fn defined_names(&mut self) -> anyhow::Result<Vec<DefinedName>> {
+        let workbook = self.open()?;
+        Ok(workbook
+            .defined_names()
+            .iter()
+            .map(|(name, formula)| DefinedName {
+                name: name.clone(),
+                refers_to: formula.clone(),
+            })
+            .collect())
+    }
+
+    /// excel tables of the workbook, only xlsx workbooks carry tables
+    pub fn tables(&mut self) -> anyhow::Result<Vec<TableInfo>> {
+        let mut workbook = self.open()?;
+        let Sheets::Xlsx(xlsx) = &mut workbook else {
+            return Ok(vec![]);
+        };
+        xlsx.load_tables()
+            .map_err(|e| anyhow!("failed to read tables: {}", e))?;
+        let names: Vec<String> = xlsx.table_names().into_iter().cloned().collect();
+        let mut tables = Vec::with_capacity(names.len());
+        for name in names {
+            let table = xlsx
+                .table_by_name_ref(&name)
+                .map_err(|e| anyhow!("failed to read table {}: {}", name, e))?;
+            let bounds = table_bounds(table.data());
+            tables.push(TableInfo {
+                name,
+                sheet: table.sheet_name().to_string(),
+                columns: table.columns().to_vec(),
+                range: bounds.map(|(start, end)| range_name(start, end)),
+                start: bounds.map(|(start, _)| start),
+                end: bounds.map(|(_, end)| end),
+            });
+        }
+        Ok(tables)
+    }
+
+    /// read an excel table or a defined name referring to a single range,
+    /// rows are keyed by the table columns or by the first row of the named
+    /// range, `sheet`, `range` and `header` are ignored
+    pub fn read_table(&mut self, name: &str, options: &ReadOptions) -> anyhow::Result<SheetData> {
+        let mut workbook = self.open()?;
+        let metadata = workbook.sheets_metadata().to_vec();
+        let sheet_index = |sheet: &str| -> anyhow::Result<usize> {
+            metadata
+                .iter()
+                .position(|s| s.name == sheet)
+                .ok_or_else(|| anyhow!("sheet not found: {}", sheet))
+        };
+
+        if let Sheets::Xlsx(xlsx) = &mut workbook {
+            xlsx.load_tables()
+                .map_err(|e| anyhow!("failed to read tables: {}", e))?;
+            // table names are case insensitive in excel
+            let table = xlsx
+                .table_names()
+                .into_iter()
+                .find(|t| t.eq_ignore_ascii_case(name))
+                .cloned();
+            if let Some(table) = table {
+                let table = xlsx
+                    .table_by_name_ref(&table)
+                    .map_err(|e| anyhow!("failed to read table {}: {}", name, e))?;
+                let index = sheet_index(table.sheet_name())?;
+                let bounds = table_bounds(table.data());
+                let columns = table.columns().to_vec();
+                let Some(bounds) = bounds else {
+                    // only the header row, the table has no data rows
+                    let formulas = options.formulas.then(Range::empty);
+                    return Ok(sheet_data(
+                        index,
+                        &metadata[index],
+                        &Range::empty(),
+                        formulas.as_ref(),
+                        &[],
+                        Some(columns),
+                        options,
+                    ));
+                };
+                return read_range(
+                    &mut workbook,
+                    index,
+                    &metadata[index],
+                    Some(bounds),
+                    Some(columns),
+                    options,
+                );
+            }
+        }
+
+        let Some((_, formula)) = workbook
+            .defined_names()
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .cloned()
+        else {
+            bail!("no table or defined name called {}", name);
+        };
+        let formula = formula.trim_start_matches('=');
+        let range_ref = match A1Range::parse(formula) {
+            Ok(r) if r.sheet.is_some() => r,
+            _ => bail!(
+                "defined name {} does not refer to a single cell range: {}",
+                name,
+                formula
+            ),
+        };
+        let index = sheet_index(range_ref.sheet.as_deref().unwrap_or_default())?;
+        let options = ReadOptions {
+            header: true,
+            ..options.clone()
+        };
+        read_range(
+            &mut workbook,
+            index,
+            &metadata[index],
+            Some((range_ref.start, range_ref.end)),
+            None,
+            &options,
+        )
+    }
+
+    /// read every sheet with its name, index, visibility and dimensions,
+    /// row objects are keyed by the absolute column letters of the sheet
+    /// unless `options.header` is set, `sheet` and `range` are ignored
+    pub fn read_sheets(&mut self, options: &ReadOptions) -> anyhow::Result<Vec<SheetData>> {
+        let mut workbook = self.open()?;
+        let metadata = workbook.sheets_metadata().to_vec();
+        let mut sheets = Vec::with_capacity(metadata.len());
+        for (index, sheet) in metadata.into_iter().enumerate() {
+            let mut range = match workbook.worksheet_range(&sheet.name) {
+                Ok(range) => range,
+                // chart and dialog sheets carry no cells
+                Err(_) if sheet.typ != SheetType::WorkSheet => Range::empty(),
+                Err(e) => bail!("failed to read sheet {}: {}", sheet.name, e),
+            };
+            let formulas = if options.formulas {
+                match workbook.worksheet_formula(&sheet.name) {
+                    Ok(formulas) => Some(formulas),
+                    Err(_) if sheet.typ != SheetType::WorkSheet => Some(Range::empty()),
+                    Err(e) => bail!("failed to read formulas of {}: {}", sheet.name, e),
+                }
+            } else {
+                None
+            };
+            let merged = if options.merged_cells || options.fill_merged {
+                merged_regions(&mut workbook, &sheet.name)?
+            } else {
+                vec![]
+            };
+            if options.fill_merged {
+                fill_merged(&mut range, &merged);
+            }
+            sheets.push(sheet_data(
+                index,
+                &sheet,
+                &range,
+                formulas.as_ref(),
+                &merged,
+                None,
+                options,
+            ));
+        }
+        Ok(sheets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_format_by_magic() {
+        let mut ods = ZIP_MAGIC.to_vec();
+        ods.extend_from_slice(b"\0\0\0\0mimetypeapplication/vnd.oasis.opendocument.spreadsheet");
+
+        assert_eq!(
+            XlsFormat::from_magic(&OLE_MAGIC, None),
+            Some(XlsFormat::Xls)
+        );
+        assert_eq!(
+            XlsFormat::from_magic(&OLE_MAGIC, Some(XlsFormat::Xlsx)),
+            Some(XlsFormat::Xls)
+        );
+        assert_eq!(
+            XlsFormat::from_magic(&ZIP_MAGIC, None),
+            Some(XlsFormat::Xlsx)
+        );
+        assert_eq!(
+            XlsFormat::from_magic(&ZIP_MAGIC, Some(XlsFormat::Xlsb)),
+            Some(XlsFormat::Xlsb)
+        );
+        assert_eq!(XlsFormat::from_magic(&ods, None), Some(XlsFormat::Ods));
+        assert_eq!(XlsFormat::from_magic(b"a,b,c\n1,2,3", None), None);
+    }
+
+    #[test]
+    fn dedupe_header_keys() {
+        let row = vec![
+            Data::String("price".to_string()),
+            Data::Empty,
+            Data::String(" price ".to_string()),
+            Data::String("price_2".to_string()),
+            Data::Int(2024),
+        ];
+        assert_eq!(
+            header_keys(&row, 1),
+            vec!["price", "C", "price_2", "price_2_2", "2024"]
+        );
+    }
+
+    #[test]
+    fn fill_merged_regions() {
+        let mut range = Range::new((0, 0), (2, 2));
+        range.set_value((0, 0), Data::String("group".to_string()));
+        range.set_value((1, 1), Data::Int(1));
+        let regions = [
+            Dimensions::new((0, 0), (0, 2)),
+            Dimensions::new((1, 1), (4, 1)),
+        ];
+        fill_merged(&mut range, &regions);
+
+        assert_eq!(
+            range.get_value((0, 2)),
+            Some(&Data::String("group".to_string()))
+        );
+        assert_eq!(range.get_value((2, 1)), Some(&Data::Int(1)));
+        assert_eq!(range.get_value((1, 0)), Some(&Data::Empty));
+        assert_eq!(range.end(), Some((2, 2)));
+        assert_eq!(MergedRegion::from(&regions[0]).range, "A1:C1");
+    }
+
+    #[test]
+    fn read_bounded_rows() {
+        let cells: Vec<Cell<Data>> = (0..100)
+            .flat_map(|r| (0..3).map(move |c| Cell::new((r, c), Data::Int((r * 10 + c) as i64))))
+            .collect();
+        let mut cells = cells.into_iter();
+        let mut read = 0;
+        let dims = Dimensions {
+            start: (0, 0),
+            end: (99, 2),
+        };
+        let range = bounded_range(dims, (2, 1), (3, 5), || {
+            read += 1;
+            Ok(cells.next())
+        })
+        .unwrap();
+        assert_eq!(range.start(), Some((2, 1)));
+        assert_eq!(range.end(), Some((3, 2)));
+        assert_eq!(range.get_value((3, 2)), Some(&Data::Int(32)));
+        // stops at the first cell of row 4
+        assert_eq!(read, 13);
+    }
+
+    #[test]
+    fn read_header_only_table() {
+        use rust_xlsxwriter::{Table, TableColumn, Workbook};
+
+        let path = std::env::temp_dir().join(format!("xls-dsl-table-{}.xlsx", std::process::id()));
+        let mut workbook = Workbook::new();
+        let sheet = workbook.add_worksheet();
+        let table = Table::new().set_name("Orders").set_columns(&[
+            TableColumn::new().set_header("Id"),
+            TableColumn::new().set_header("Name"),
+        ]);
+        // excel keeps one blank data row in a table without data
+        sheet.add_table(2, 1, 3, 2, &table).unwrap();
+        workbook.save(&path).unwrap();
+
+        let mut parse = ParseXls {
+            xls_path: path.to_string_lossy().to_string(),
+        };
+        let data = parse.read_table("Orders", &ReadOptions::default()).unwrap();
+        assert!(data.rows.is_empty());
+        assert_eq!(
+            data.headers,
+            Some(vec!["Id".to_string(), "Name".to_string()])
+        );
+        let tables = parse.tables().unwrap();
+        assert_eq!(tables[0].range, None);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn format_iso_duration() {
+        let d = chrono::Duration::milliseconds;
+        assert_eq!(iso_duration(d(5_400_000)), "PT1H30M");
+        assert_eq!(iso_duration(d(0)), "PT0S");
+        assert_eq!(iso_duration(d(-500)), "-PT0.5S");
+        assert_eq!(iso_duration(d(90_061_250)), "PT25H1M1.25S");
+    }
+
+    #[test]
+    fn detect_format_by_extension() {
+        assert_eq!(XlsFormat::from_extension("a/b.XLS"), Some(XlsFormat::Xls));
+        assert_eq!(XlsFormat::from_extension("b.xlsm"), Some(XlsFormat::Xlsx));
+        assert_eq!(XlsFormat::from_extension("b.xlsb"), Some(XlsFormat::Xlsb));
+        assert_eq!(XlsFormat::from_extension("b.ods"), Some(XlsFormat::Ods));
+        assert_eq!(XlsFormat::from_extension("b.csv"), None);
+    }
 }
@@ -1 +1,5 @@
+pub(crate) mod delimited;
 pub(crate) mod lib;
+pub(crate) mod reference;
+pub(crate) mod stream;
+pub(crate) mod template;
@@ -0,0 +1,160 @@
+use anyhow::bail;
+
+/// A1 style range such as `Sheet1!B2:F500`, `'My Sheet'!A:C` or `B2`,
+/// rows and columns are zero based and inclusive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct A1Range {
+    pub sheet: Option<String>,
+    pub start: (u32, u32),
+    pub end: (u32, u32),
+}
+
+/// number of columns in an excel sheet, `A` to `XFD`
+pub const MAX_COLUMNS: u32 = 16_384;
+
+/// convert a zero based column index to its letters, 0 is `A` and 16383 is `XFD`
+pub fn column_name(index: u32) -> String {
+    let mut n = index as u64 + 1;
+    let mut letters = Vec::new();
+    while n > 0 {
+        let rem = ((n - 1) % 26) as u8;
+        letters.push((b'A' + rem) as char);
+        n = (n - 1) / 26;
+    }
+    letters.iter().rev().collect()
+}
+
+/// convert column letters to a zero based column index, `A` is 0
+pub fn column_number(letters: &str) -> Option<u32> {
+    if letters.is_empty() {
+        return None;
+    }
+    let mut n: u32 = 0;
+    for c in letters.chars() {
+        if !c.is_ascii_alphabetic() {
+            return None;
+        }
+        let v = (c.to_ascii_uppercase() as u8 - b'A') as u32 + 1;
+        n = n.checked_mul(26)?.checked_add(v)?;
+    }
+    Some(n - 1)
+}
+
+/// A1 reference of a zero based range, `B2` for a single cell, otherwise `B2:F500`
+pub fn range_name(start: (u32, u32), end: (u32, u32)) -> String {
+    let first = format!("{}{}", column_name(start.1), start.0 + 1);
+    if start == end {
+        return first;
+    }
+    format!("{}:{}{}", first, column_name(end.1), end.0 + 1)
+}
+
+/// parse one side of a range, `B2`, `$B$2`, `B` or `2`, into (row, column)
+fn parse_cell(s: &str) -> Option<(Option<u32>, Option<u32>)> {
+    let s = s.replace('$', "");
+    let split = s.find(|c: char| c.is_ascii_digit()).unwrap_or(s.len());
+    let (letters, digits) = s.split_at(split);
+    let col = if letters.is_empty() {
+        None
+    } else {
+        Some(column_number(letters)?)
+    };
+    let row = if digits.is_empty() {
+        None
+    } else {
+        let row: u32 = digits.parse().ok()?;
+        if row == 0 {
+            return None;
+        }
+        Some(row - 1)
+    };
+    if col.is_none() && row.is_none() {
+        return None;
+    }
+    Some((row, col))
+}
+
+/// split `Sheet1!B2:F500` into the unquoted sheet name and the cell part
+fn split_sheet(s: &str) -> (Option<String>, &str) {
+    match s.rfind('!') {
+        Some(i) => {
+            let sheet = &s[..i];
+            let sheet = match sheet.strip_prefix('\'').and_then(|x| x.strip_suffix('\'')) {
+                Some(quoted) => quoted.replace("''", "'"),
+                None => sheet.to_string(),
+            };
+            (Some(sheet), &s[i + 1..])
+        }
+        None => (None, s),
+    }
+}
+
+impl A1Range {
+    pub fn parse(s: &str) -> anyhow::Result<Self> {
+        let (sheet, cells) = split_sheet(s.trim());
+        let (first, last) = match cells.split_once(':') {
+            Some((a, b)) => (a, b),
+            None => (cells, cells),
+        };
+        let (Some(first), Some(last)) = (parse_cell(first), parse_cell(last)) else {
+            bail!("invalid cell range: {}", s);
+        };
+        // a missing row or column means the whole column or row, e.g. `A:C` or `2:10`
+        let start = (first.0.unwrap_or(0), first.1.unwrap_or(0));
+        let end = (last.0.unwrap_or(u32::MAX), last.1.unwrap_or(u32::MAX));
+        if start.0 > end.0 || start.1 > end.1 {
+            bail!("invalid cell range: {}", s);
+        }
+        Ok(A1Range { sheet, start, end })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_columns() {
+        for (index, name) in [
+            (0, "A"),
+            (25, "Z"),
+            (26, "AA"),
+            (701, "ZZ"),
+            (702, "AAA"),
+            (16383, "XFD"),
+        ] {
+            assert_eq!(column_name(index), name);
+            assert_eq!(column_number(name), Some(index));
+        }
+        assert_eq!(column_number("xfd"), Some(MAX_COLUMNS - 1));
+        assert_eq!(column_number(""), None);
+        assert_eq!(column_number("A1"), None);
+    }
+
+    #[test]
+    fn parse_ranges() {
+        assert_eq!(
+            A1Range::parse("Sheet1!B2:F500").unwrap(),
+            A1Range {
+                sheet: Some("Sheet1".to_string()),
+                start: (1, 1),
+                end: (499, 5)
+            }
+        );
+        assert_eq!(
+            A1Range::parse("'It''s'!$A$1").unwrap(),
+            A1Range {
+                sheet: Some("It's".to_string()),
+                start: (0, 0),
+                end: (0, 0)
+            }
+        );
+        assert_eq!(A1Range::parse("A:C").unwrap().end, (u32::MAX, 2));
+        assert_eq!(A1Range::parse("2:10").unwrap().start, (1, 0));
+        assert_eq!(range_name((1, 1), (499, 5)), "B2:F500");
+        assert_eq!(range_name((0, 0), (0, 0)), "A1");
+        assert!(A1Range::parse("F5:B2").is_err());
+        assert!(A1Range::parse("A0").is_err());
+        assert!(A1Range::parse("1A").is_err());
+    }
+}
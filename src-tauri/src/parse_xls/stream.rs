@@ -0,0 +1,215 @@
+use std::io::{Read, Seek};
+use std::thread;
+
+use anyhow::{anyhow, bail};
+use calamine::{Data, Reader, Sheets, Xlsx};
+use tokio::sync::mpsc::{self, Receiver, Sender};
+
+use super::lib::{
+    open_workbook_any, select_sheet, slice_range, JsonObject, ReadOptions, RowShaper,
+};
+use super::reference::A1Range;
+
+pub type RowChunk = anyhow::Result<Vec<JsonObject>>;
+
+/// Reads the rows of one sheet on a background thread and hands them out in
+/// chunks of `chunk_size` rows. The channel holds at most two chunks, so the
+/// reader waits while the script is busy and stops once the receiver is dropped.
+///
+/// xlsx sheets are parsed cell by cell in constant memory, the xls, xlsb and
+/// ods readers can only load a whole sheet at once.
+pub fn stream_rows(path: String, options: ReadOptions, chunk_size: usize) -> Receiver<RowChunk> {
+    let (tx, rx) = mpsc::channel(2);
+    thread::spawn(move || {
+        let mut sink = ChunkSink {
+            tx: &tx,
+            chunk: Vec::new(),
+            chunk_size: chunk_size.max(1),
+        };
+        match produce(&path, &options, &mut sink) {
+            Ok(_) => {
+                sink.flush();
+            }
+            Err(e) => {
+                let _ = tx.blocking_send(Err(e));
+            }
+        }
+    });
+    rx
+}
+
+/// Collects row objects into chunks, `push` and `flush` return `false` once
+/// the receiving side is gone
+struct ChunkSink<'a> {
+    tx: &'a Sender<RowChunk>,
+    chunk: Vec<JsonObject>,
+    chunk_size: usize,
+}
+
+impl ChunkSink<'_> {
+    fn push(&mut self, row: JsonObject) -> bool {
+        self.chunk.push(row);
+        if self.chunk.len() >= self.chunk_size {
+            return self.flush();
+        }
+        true
+    }
+
+    fn flush(&mut self) -> bool {
+        if self.chunk.is_empty() {
+            return true;
+        }
+        let chunk = std::mem::replace(&mut self.chunk, Vec::with_capacity(self.chunk_size));
+        self.tx.blocking_send(Ok(chunk)).is_ok()
+    }
+}
+
+/// rows are shaped one at a time, so options that need the whole sheet
+/// can't be honoured while streaming
+fn check_options(options: &ReadOptions) -> anyhow::Result<()> {
+    let unsupported: Vec<&str> = [
+        (options.formulas, "formulas"),
+        (options.merged_cells, "mergedCells"),
+        (options.fill_merged, "fillMerged"),
+    ]
+    .into_iter()
+    .filter_map(|(set, name)| set.then_some(name))
+    .collect();
+    if !unsupported.is_empty() {
+        bail!(
+            "{} can't be used when streaming rows, use read_sheet instead",
+            unsupported.join(", ")
+        );
+    }
+    Ok(())
+}
+
+fn produce(path: &str, options: &ReadOptions, sink: &mut ChunkSink) -> anyhow::Result<()> {
+    check_options(options)?;
+    let mut workbook = open_workbook_any(path)?;
+    let range_ref = options.range.as_deref().map(A1Range::parse).transpose()?;
+    let metadata = workbook.sheets_metadata().to_vec();
+    let index = select_sheet(&metadata, options, range_ref.as_ref())?;
+    let name = metadata[index].name.clone();
+
+    match &mut workbook {
+        Sheets::Xlsx(xlsx) => stream_xlsx(xlsx, &name, range_ref.as_ref(), options, sink),
+        other => {
+            let mut range = other
+                .worksheet_range(&name)
+                .map_err(|e| anyhow!("failed to read sheet {}: {}", name, e))?;
+            if let Some(r) = &range_ref {
+                range = slice_range(&range, r.start, r.end);
+            }
+            let first_col = range.start().map(|(_, c)| c as usize).unwrap_or(0);
+            let mut shaper = RowShaper::new(options, first_col);
+            for row in range.rows() {
+                if let Some(obj) = shaper.shape(row) {
+                    if !sink.push(obj) {
+                        break;
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+fn stream_xlsx<RS: Read + Seek>(
+    xlsx: &mut Xlsx<RS>,
+    name: &str,
+    range_ref: Option<&A1Range>,
+    options: &ReadOptions,
+    sink: &mut ChunkSink,
+) -> anyhow::Result<()> {
+    let mut cells = xlsx
+        .worksheet_cells_reader(name)
+        .map_err(|e| anyhow!("failed to read sheet {}: {}", name, e))?;
+    let dims = cells.dimensions();
+    let (start, end) = match range_ref {
+        Some(r) => (
+            (r.start.0.max(dims.start.0), r.start.1.max(dims.start.1)),
+            r.end,
+        ),
+        None => (dims.start, (u32::MAX, u32::MAX)),
+    };
+    // rows are padded to the declared sheet width so every row has the same keys
+    let mut width = if dims.end.1 >= start.1 {
+        (dims.end.1.min(end.1) - start.1 + 1) as usize
+    } else {
+        0
+    };
+
+    let mut shaper = RowShaper::new(options, start.1 as usize);
+    let mut emit = |row: &mut Vec<Data>, width: usize| -> bool {
+        row.resize(width.max(row.len()), Data::Empty);
+        let cells = std::mem::take(row);
+        match shaper.shape(&cells) {
+            Some(obj) => sink.push(obj),
+            None => true,
+        }
+    };
+
+    let mut row: Vec<Data> = Vec::new();
+    let mut current: Option<u32> = None;
+    while let Some(cell) = cells
+        .next_cell()
+        .map_err(|e| anyhow!("failed to read sheet {}: {}", name, e))?
+    {
+        let (r, c) = cell.get_position();
+        if r < start.0 || c < start.1 || c > end.1 {
+            continue;
+        }
+        if r > end.0 {
+            break;
+        }
+        // an explicit range keeps its leading empty rows, like `read_sheet`
+        let mut at = current.unwrap_or(if range_ref.is_some() { start.0 } else { r });
+        // emit the finished row and the empty rows of any gap before this cell
+        while at < r {
+            if !emit(&mut row, width) {
+                return Ok(());
+            }
+            at += 1;
+        }
+        current = Some(at);
+
+        let offset = (c - start.1) as usize;
+        if row.len() <= offset {
+            row.resize(offset + 1, Data::Empty);
+        }
+        width = width.max(row.len());
+        row[offset] = cell.get_value().clone().into();
+    }
+    if current.is_some() {
+        emit(&mut row, width);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reject_whole_sheet_options() {
+        assert!(check_options(&ReadOptions::default()).is_ok());
+        let options = ReadOptions {
+            formulas: true,
+            fill_merged: true,
+            ..Default::default()
+        };
+        let err = check_options(&options).unwrap_err().to_string();
+        assert!(
+            err.starts_with("formulas, fillMerged can't be used"),
+            "{}",
+            err
+        );
+
+        // checked before the workbook is opened, the error reaches the script
+        // through the channel instead of rows
+        let mut rx = stream_rows("missing.xlsx".to_string(), options, 10);
+        let err = rx.blocking_recv().unwrap().unwrap_err().to_string();
+        assert!(err.contains("can't be used when streaming"), "{}", err);
+    }
+}
@@ -0,0 +1,896 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek, Write};
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context};
+use quick_xml::escape::unescape;
+use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::{Reader, Writer};
+use serde::Serialize;
+use serde_json::Value;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use super::lib::{excel_serial, parse_iso_date, JsonObject};
+use super::reference::{column_number, range_name, A1Range};
+
+type Cell = (u32, u32);
+
+/// Outcome of `fill_template`
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FillReport {
+    /// number of cells written
+    pub cells: usize,
+    /// placeholders found in the template without a value, left untouched
+    pub missing: Vec<String>,
+}
+
+/// A sheet of the workbook and the zip entry holding its xml
+struct SheetPart {
+    name: String,
+    path: String,
+}
+
+/// Sheets and defined names read from `xl/workbook.xml`
+struct WorkbookParts {
+    sheets: Vec<SheetPart>,
+    /// (name, formula), workbook level names first
+    names: Vec<(String, String)>,
+}
+
+fn read_entry(archive: &mut ZipArchive<BufReader<File>>, name: &str) -> anyhow::Result<Vec<u8>> {
+    let mut entry = archive
+        .by_name(name)
+        .map_err(|e| anyhow!("invalid xlsx template, missing {}: {}", name, e))?;
+    let mut buf = Vec::with_capacity(entry.size() as usize);
+    entry.read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+fn attr(e: &BytesStart, key: &[u8]) -> anyhow::Result<Option<String>> {
+    for a in e.attributes() {
+        let a = a?;
+        if a.key.as_ref() == key || a.key.local_name().as_ref() == key {
+            let value = String::from_utf8_lossy(&a.value).into_owned();
+            return Ok(Some(unescape(&value)?.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+/// `x:` for `<x:row>`, empty without a namespace prefix
+fn prefix_of(e: &BytesStart) -> String {
+    match e.name().prefix() {
+        Some(p) => format!("{}:", String::from_utf8_lossy(p.as_ref())),
+        None => String::new(),
+    }
+}
+
+/// all text of a `<si>` or `<is>` element, rich text runs are concatenated
+fn collect_text(events: &[Event]) -> anyhow::Result<String> {
+    let mut text = String::new();
+    let mut in_t = false;
+    let mut in_phonetic = false;
+    for ev in events {
+        match ev {
+            Event::Start(e) if e.local_name().as_ref() == b"t" => in_t = true,
+            Event::End(e) if e.local_name().as_ref() == b"t" => in_t = false,
+            // phonetic runs hold furigana that excel doesn't display
+            Event::Start(e) if e.local_name().as_ref() == b"rPh" => in_phonetic = true,
+            Event::End(e) if e.local_name().as_ref() == b"rPh" => in_phonetic = false,
+            Event::Text(t) if in_t && !in_phonetic => text.push_str(&t.unescape()?),
+            Event::CData(t) if in_t && !in_phonetic => {
+                text.push_str(&String::from_utf8_lossy(t.as_ref()))
+            }
+            _ => {}
+        }
+    }
+    Ok(text)
+}
+
+fn parse_shared_strings(xml: &[u8]) -> anyhow::Result<Vec<String>> {
+    let mut reader = Reader::from_reader(xml);
+    let mut strings = Vec::new();
+    let mut item: Option<Vec<Event>> = None;
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) if e.local_name().as_ref() == b"si" => item = Some(Vec::new()),
+            Event::Empty(e) if e.local_name().as_ref() == b"si" => strings.push(String::new()),
+            Event::End(e) if e.local_name().as_ref() == b"si" => {
+                strings.push(collect_text(&item.take().unwrap_or_default())?)
+            }
+            Event::Eof => break,
+            ev => {
+                if let Some(events) = item.as_mut() {
+                    events.push(ev);
+                }
+            }
+        }
+    }
+    Ok(strings)
+}
+
+fn parse_workbook(workbook: &[u8], rels: &[u8]) -> anyhow::Result<WorkbookParts> {
+    let mut targets = HashMap::new();
+    let mut reader = Reader::from_reader(rels);
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"Relationship" => {
+                if let (Some(id), Some(target)) = (attr(&e, b"Id")?, attr(&e, b"Target")?) {
+                    let path = match target.strip_prefix('/') {
+                        Some(absolute) => absolute.to_string(),
+                        None => format!("xl/{}", target),
+                    };
+                    targets.insert(id, path);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    let mut parts = WorkbookParts {
+        sheets: Vec::new(),
+        names: Vec::new(),
+    };
+    let mut local_names = Vec::new();
+    let mut reader = Reader::from_reader(workbook);
+    let mut name: Option<(String, bool)> = None;
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"sheet" => {
+                let sheet = attr(&e, b"name")?.unwrap_or_default();
+                // the relationship id is namespaced, `r:id`
+                if let Some(path) = attr(&e, b"id")?.and_then(|id| targets.get(&id).cloned()) {
+                    parts.sheets.push(SheetPart { name: sheet, path });
+                }
+            }
+            Event::Start(e) if e.local_name().as_ref() == b"definedName" => {
+                let local = attr(&e, b"localSheetId")?.is_some();
+                name = attr(&e, b"name")?.map(|n| (n, local));
+            }
+            Event::Text(t) => {
+                if let Some((n, local)) = name.take() {
+                    let formula = t.unescape()?.into_owned();
+                    if local {
+                        local_names.push((n, formula));
+                    } else {
+                        parts.names.push((n, formula));
+                    }
+                }
+            }
+            Event::End(e) if e.local_name().as_ref() == b"definedName" => name = None,
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    parts.names.extend(local_names);
+    Ok(parts)
+}
+
+/// value at `key`, a dotted key such as `order.total` walks nested objects
+fn lookup<'a>(values: &'a JsonObject, key: &str) -> Option<&'a Value> {
+    if let Some(v) = values.get(key) {
+        return Some(v);
+    }
+    let mut parts = key.split('.');
+    let mut current = values.get(parts.next()?)?;
+    for part in parts {
+        current = match current {
+            Value::Object(obj) => obj.get(part)?,
+            Value::Array(items) => items.get(part.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+/// text used when a value is substituted into a longer string
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Object(obj) => match obj.get("$date").and_then(Value::as_str) {
+            Some(date) => date
+                .trim_end_matches(".000")
+                .trim_end_matches("T00:00:00")
+                .replace('T', " "),
+            None => value.to_string(),
+        },
+        _ => value.to_string(),
+    }
+}
+
+/// byte ranges and keys of the `{{key}}` placeholders in `text`
+fn placeholders(text: &str) -> Vec<(usize, usize, &str)> {
+    let mut found = Vec::new();
+    let mut from = 0;
+    while let Some(open) = text[from..].find("{{") {
+        let open = from + open;
+        let Some(close) = text[open + 2..].find("}}") else {
+            break;
+        };
+        let close = open + 2 + close;
+        found.push((open, close + 2, text[open + 2..close].trim()));
+        from = close + 2;
+    }
+    found
+}
+
+/// The value a cell should hold after its placeholders are filled, `None`
+/// when the cell has no placeholder with a value
+fn fill_text(text: &str, values: &JsonObject, missing: &mut Vec<String>) -> Option<Value> {
+    let found = placeholders(text);
+    if found.is_empty() {
+        return None;
+    }
+    for (_, _, key) in &found {
+        if lookup(values, key).is_none() && !missing.iter().any(|m| m == key) {
+            missing.push(key.to_string());
+        }
+    }
+    // a cell holding just one placeholder takes the value with its type
+    if let [(start, end, key)] = found[..] {
+        if text[..start].trim().is_empty() && text[end..].trim().is_empty() {
+            return lookup(values, key).cloned();
+        }
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    let mut changed = false;
+    for (start, end, key) in found {
+        out.push_str(&text[last..start]);
+        match lookup(values, key) {
+            Some(v) => {
+                out.push_str(&value_text(v));
+                changed = true;
+            }
+            None => out.push_str(&text[start..end]),
+        }
+        last = end;
+    }
+    out.push_str(&text[last..]);
+    changed.then_some(Value::String(out))
+}
+
+/// write `<c>` for `value`, keeping the style of the cell it replaces
+fn write_cell<W: Write>(
+    writer: &mut Writer<W>,
+    prefix: &str,
+    cell: Cell,
+    style: Option<&str>,
+    value: &Value,
+) -> anyhow::Result<()> {
+    let tag = format!("{}c", prefix);
+    let mut start = BytesStart::new(tag.as_str());
+    start.push_attribute(("r", range_name(cell, cell).as_str()));
+    if let Some(s) = style {
+        start.push_attribute(("s", s));
+    }
+    let elem = |name: &str| format!("{}{}", prefix, name);
+    let write_text = |writer: &mut Writer<W>, name: &str, text: &str| -> anyhow::Result<()> {
+        let mut e = BytesStart::new(elem(name));
+        if name == "t" {
+            e.push_attribute(("xml:space", "preserve"));
+        }
+        writer.write_event(Event::Start(e))?;
+        writer.write_event(Event::Text(BytesText::new(text)))?;
+        writer.write_event(Event::End(BytesEnd::new(elem(name))))?;
+        Ok(())
+    };
+
+    match value {
+        Value::Null => {
+            writer.write_event(Event::Empty(start))?;
+        }
+        Value::Bool(b) => {
+            start.push_attribute(("t", "b"));
+            writer.write_event(Event::Start(start))?;
+            write_text(writer, "v", if *b { "1" } else { "0" })?;
+            writer.write_event(Event::End(BytesEnd::new(tag.as_str())))?;
+        }
+        Value::Number(n) => {
+            writer.write_event(Event::Start(start))?;
+            write_text(writer, "v", &n.to_string())?;
+            writer.write_event(Event::End(BytesEnd::new(tag.as_str())))?;
+        }
+        Value::Object(obj) if obj.contains_key("$date") => {
+            let date = obj.get("$date").and_then(Value::as_str).unwrap_or_default();
+            let serial = excel_serial(parse_iso_date(date)?);
+            writer.write_event(Event::Start(start))?;
+            write_text(writer, "v", &serial.to_string())?;
+            writer.write_event(Event::End(BytesEnd::new(tag.as_str())))?;
+        }
+        Value::Object(obj) if obj.contains_key("formula") => {
+            let formula = obj
+                .get("formula")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            writer.write_event(Event::Start(start))?;
+            write_text(writer, "f", formula.trim_start_matches('='))?;
+            writer.write_event(Event::End(BytesEnd::new(tag.as_str())))?;
+        }
+        other => {
+            let text = match other {
+                Value::String(s) => Cow::Borrowed(s.as_str()),
+                _ => Cow::Owned(other.to_string()),
+            };
+            start.push_attribute(("t", "inlineStr"));
+            writer.write_event(Event::Start(start))?;
+            writer.write_event(Event::Start(BytesStart::new(elem("is"))))?;
+            write_text(writer, "t", &text)?;
+            writer.write_event(Event::End(BytesEnd::new(elem("is"))))?;
+            writer.write_event(Event::End(BytesEnd::new(tag.as_str())))?;
+        }
+    }
+    Ok(())
+}
+
+/// position of a cell from its `r` attribute, e.g. `B3`
+fn parse_cell_ref(r: &str) -> Option<Cell> {
+    let split = r.find(|c: char| c.is_ascii_digit())?;
+    let col = column_number(&r[..split])?;
+    let row: u32 = r[split..].parse().ok()?;
+    Some((row.checked_sub(1)?, col))
+}
+
+/// Rewrites the cells of one worksheet, cells named in `pending` are set
+/// and created when missing, placeholder cells anywhere in the sheet are filled
+struct SheetFiller<'a, W: Write> {
+    writer: Writer<W>,
+    pending: BTreeMap<Cell, Value>,
+    shared: &'a [String],
+    values: &'a JsonObject,
+    report: &'a mut FillReport,
+    prefix: String,
+}
+
+impl<W: Write> SheetFiller<'_, W> {
+    /// write the pending cells of `row` left of `before`, all of them when `None`
+    fn flush_cells(&mut self, row: u32, before: Option<u32>) -> anyhow::Result<()> {
+        let cells: Vec<Cell> = self
+            .pending
+            .range((row, 0)..=(row, u32::MAX))
+            .map(|(cell, _)| *cell)
+            .filter(|(_, col)| before.is_none_or(|b| *col < b))
+            .collect();
+        for cell in cells {
+            if let Some(value) = self.pending.remove(&cell) {
+                write_cell(&mut self.writer, &self.prefix, cell, None, &value)?;
+                self.report.cells += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// write new `<row>` elements for pending cells above `before`, all when `None`
+    fn flush_rows(&mut self, before: Option<u32>) -> anyhow::Result<()> {
+        let rows: BTreeSet<u32> = self
+            .pending
+            .keys()
+            .map(|(row, _)| *row)
+            .filter(|row| before.is_none_or(|b| *row < b))
+            .collect();
+        let tag = format!("{}row", self.prefix);
+        for row in rows {
+            let mut start = BytesStart::new(tag.as_str());
+            start.push_attribute(("r", (row + 1).to_string().as_str()));
+            self.writer.write_event(Event::Start(start))?;
+            self.flush_cells(row, None)?;
+            self.writer
+                .write_event(Event::End(BytesEnd::new(tag.as_str())))?;
+        }
+        Ok(())
+    }
+
+    /// handle one `<c>` element, `inner` holds its child events
+    fn cell(&mut self, cell: Cell, start: &BytesStart, inner: &[Event]) -> anyhow::Result<()> {
+        let style = attr(start, b"s")?;
+        let prefix = prefix_of(start);
+        if let Some(value) = self.pending.remove(&cell) {
+            write_cell(&mut self.writer, &prefix, cell, style.as_deref(), &value)?;
+            self.report.cells += 1;
+            return Ok(());
+        }
+
+        let text = match attr(start, b"t")?.as_deref() {
+            Some("s") => {
+                let mut in_v = false;
+                let mut index = None;
+                for ev in inner {
+                    match ev {
+                        Event::Start(e) if e.local_name().as_ref() == b"v" => in_v = true,
+                        Event::End(e) if e.local_name().as_ref() == b"v" => in_v = false,
+                        Event::Text(t) if in_v => index = t.unescape()?.trim().parse().ok(),
+                        _ => {}
+                    }
+                }
+                index.and_then(|i: usize| self.shared.get(i).cloned())
+            }
+            Some("inlineStr") => Some(collect_text(inner)?),
+            _ => None,
+        };
+        let filled = text
+            .as_deref()
+            .and_then(|t| fill_text(t, self.values, &mut self.report.missing));
+        match filled {
+            Some(value) => {
+                write_cell(&mut self.writer, &prefix, cell, style.as_deref(), &value)?;
+                self.report.cells += 1;
+            }
+            None if inner.is_empty() => {
+                self.writer.write_event(Event::Empty(start.clone()))?;
+            }
+            None => {
+                let name = String::from_utf8_lossy(start.name().as_ref()).into_owned();
+                self.writer.write_event(Event::Start(start.clone()))?;
+                for ev in inner {
+                    self.writer.write_event(ev)?;
+                }
+                self.writer
+                    .write_event(Event::End(BytesEnd::new(name.as_str())))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `<dimension>` with its range widened to cover `extent`, cells set through
+/// defined names may lie outside the range the template used
+fn widen_dimension(e: &BytesStart, extent: (Cell, Cell)) -> anyhow::Result<BytesStart<'static>> {
+    let (mut start, mut end) = extent;
+    if let Some(used) = attr(e, b"ref")?.and_then(|r| A1Range::parse(&r).ok()) {
+        start = (start.0.min(used.start.0), start.1.min(used.start.1));
+        end = (end.0.max(used.end.0), end.1.max(used.end.1));
+    }
+    let mut dimension = BytesStart::new(String::from_utf8_lossy(e.name().as_ref()).into_owned());
+    for a in e.attributes() {
+        let a = a?;
+        if a.key.local_name().as_ref() != b"ref" {
+            dimension.push_attribute(a);
+        }
+    }
+    dimension.push_attribute(("ref", range_name(start, end).as_str()));
+    Ok(dimension)
+}
+
+/// fill one worksheet, returns `None` when nothing in it changed
+fn fill_sheet(
+    xml: &[u8],
+    named: BTreeMap<Cell, Value>,
+    shared: &[String],
+    values: &JsonObject,
+    report: &mut FillReport,
+) -> anyhow::Result<Option<Vec<u8>>> {
+    let before = report.cells;
+    let extent = named
+        .keys()
+        .fold(None, |extent: Option<(Cell, Cell)>, &(r, c)| {
+            Some(match extent {
+                Some((start, end)) => (
+                    (start.0.min(r), start.1.min(c)),
+                    (end.0.max(r), end.1.max(c)),
+                ),
+                None => ((r, c), (r, c)),
+            })
+        });
+    let mut reader = Reader::from_reader(xml);
+    let mut filler = SheetFiller {
+        writer: Writer::new(Vec::with_capacity(xml.len() + 1024)),
+        pending: named,
+        shared,
+        values,
+        report,
+        prefix: String::new(),
+    };
+    let mut row: Option<u32> = None;
+    let mut next_row = 0;
+    let mut next_col = 0;
+
+    loop {
+        let ev = reader.read_event()?;
+        match &ev {
+            Event::Empty(e) if e.local_name().as_ref() == b"dimension" => match extent {
+                Some(extent) => filler
+                    .writer
+                    .write_event(Event::Empty(widen_dimension(e, extent)?))?,
+                None => filler.writer.write_event(&ev)?,
+            },
+            Event::Start(e) if e.local_name().as_ref() == b"sheetData" => {
+                filler.prefix = prefix_of(e);
+                filler.writer.write_event(&ev)?;
+            }
+            Event::Empty(e) if e.local_name().as_ref() == b"sheetData" => {
+                filler.prefix = prefix_of(e);
+                let name = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+                filler
+                    .writer
+                    .write_event(Event::Start(BytesStart::new(name.as_str())))?;
+                filler.flush_rows(None)?;
+                filler
+                    .writer
+                    .write_event(Event::End(BytesEnd::new(name.as_str())))?;
+            }
+            Event::End(e) if e.local_name().as_ref() == b"sheetData" => {
+                filler.flush_rows(None)?;
+                filler.writer.write_event(&ev)?;
+            }
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"row" => {
+                let r = match attr(e, b"r")?.and_then(|r| r.parse::<u32>().ok()) {
+                    Some(r) if r > 0 => r - 1,
+                    _ => next_row,
+                };
+                next_row = r + 1;
+                next_col = 0;
+                filler.flush_rows(Some(r))?;
+                if matches!(ev, Event::Empty(_)) {
+                    let has_pending = filler
+                        .pending
+                        .range((r, 0)..=(r, u32::MAX))
+                        .next()
+                        .is_some();
+                    if has_pending {
+                        let name = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+                        filler.writer.write_event(Event::Start(e.clone()))?;
+                        filler.flush_cells(r, None)?;
+                        filler
+                            .writer
+                            .write_event(Event::End(BytesEnd::new(name.as_str())))?;
+                    } else {
+                        filler.writer.write_event(&ev)?;
+                    }
+                } else {
+                    row = Some(r);
+                    filler.writer.write_event(&ev)?;
+                }
+            }
+            Event::End(e) if e.local_name().as_ref() == b"row" => {
+                if let Some(r) = row.take() {
+                    filler.flush_cells(r, None)?;
+                }
+                filler.writer.write_event(&ev)?;
+            }
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"c" => {
+                let r = row.unwrap_or(next_row.saturating_sub(1));
+                let cell = attr(e, b"r")?
+                    .and_then(|r| parse_cell_ref(&r))
+                    .unwrap_or((r, next_col));
+                next_col = cell.1 + 1;
+                let mut inner = Vec::new();
+                if matches!(ev, Event::Start(_)) {
+                    loop {
+                        match reader.read_event()? {
+                            Event::End(end) if end.local_name().as_ref() == b"c" => break,
+                            Event::Eof => bail!("invalid worksheet xml, unclosed cell"),
+                            inner_ev => inner.push(inner_ev),
+                        }
+                    }
+                }
+                filler.flush_cells(cell.0, Some(cell.1))?;
+                filler.cell(cell, e, &inner)?;
+            }
+            Event::Eof => break,
+            _ => filler.writer.write_event(&ev)?,
+        }
+    }
+
+    let changed = filler.report.cells > before;
+    let xml = filler.writer.into_inner();
+    Ok(changed.then_some(xml))
+}
+
+/// set `fullCalcOnLoad` so excel recalculates formulas that depend on filled cells
+fn force_recalc(workbook: &[u8]) -> anyhow::Result<Vec<u8>> {
+    // elements that follow `calcPr` in the workbook schema
+    const AFTER_CALC: [&[u8]; 9] = [
+        b"oleSize",
+        b"customWorkbookViews",
+        b"pivotCaches",
+        b"smartTagPr",
+        b"smartTagTypes",
+        b"webPublishing",
+        b"fileRecoveryPr",
+        b"webPublishObjects",
+        b"extLst",
+    ];
+    let mut reader = Reader::from_reader(workbook);
+    let mut writer = Writer::new(Vec::with_capacity(workbook.len() + 64));
+    let mut done = false;
+    let mut prefix = String::new();
+    loop {
+        let ev = reader.read_event()?;
+        match &ev {
+            Event::Start(e) if e.local_name().as_ref() == b"workbook" => {
+                prefix = prefix_of(e);
+                writer.write_event(&ev)?;
+            }
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"calcPr" => {
+                let mut calc = BytesStart::new(String::from_utf8_lossy(e.name().as_ref()).into_owned());
+                for a in e.attributes() {
+                    let a = a?;
+                    if a.key.local_name().as_ref() != b"fullCalcOnLoad" {
+                        calc.push_attribute(a);
+                    }
+                }
+                calc.push_attribute(("fullCalcOnLoad", "1"));
+                writer.write_event(if matches!(ev, Event::Start(_)) {
+                    Event::Start(calc)
+                } else {
+                    Event::Empty(calc)
+                })?;
+                done = true;
+            }
+            Event::Start(e) | Event::Empty(e)
+                if !done && AFTER_CALC.contains(&e.local_name().as_ref()) =>
+            {
+                let mut calc = BytesStart::new(format!("{}calcPr", prefix));
+                calc.push_attribute(("fullCalcOnLoad", "1"));
+                writer.write_event(Event::Empty(calc))?;
+                writer.write_event(&ev)?;
+                done = true;
+            }
+            Event::End(e) if !done && e.local_name().as_ref() == b"workbook" => {
+                let mut calc = BytesStart::new(format!("{}calcPr", prefix));
+                calc.push_attribute(("fullCalcOnLoad", "1"));
+                writer.write_event(Event::Empty(calc))?;
+                writer.write_event(&ev)?;
+                done = true;
+            }
+            Event::Eof => break,
+            _ => writer.write_event(&ev)?,
+        }
+    }
+    Ok(writer.into_inner())
+}
+
+/// drop the elements referring to `xl/calcChain.xml`, excel rebuilds the
+/// chain and reports a damaged file when it lists cells that lost their formula
+fn drop_calc_chain(xml: &[u8], element: &[u8], key: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut reader = Reader::from_reader(xml);
+    let mut writer = Writer::new(Vec::with_capacity(xml.len()));
+    loop {
+        let ev = reader.read_event()?;
+        match &ev {
+            Event::Empty(e) if e.local_name().as_ref() == element => {
+                let target = attr(e, key)?.unwrap_or_default();
+                if !target.ends_with("calcChain.xml") {
+                    writer.write_event(&ev)?;
+                }
+            }
+            Event::Eof => break,
+            _ => writer.write_event(&ev)?,
+        }
+    }
+    Ok(writer.into_inner())
+}
+
+/// cells of a defined name's range to set from `value`, an array of arrays
+/// fills rows from the top-left cell, a flat array fills down a single column
+/// range and across otherwise, anything else sets the top-left cell
+fn named_cells(range: &A1Range, value: &Value, cells: &mut BTreeMap<Cell, Value>) {
+    let (row, col) = range.start;
+    match value {
+        Value::Array(items) if items.iter().all(Value::is_array) => {
+            for (i, r) in items.iter().enumerate() {
+                for (j, v) in r.as_array().into_iter().flatten().enumerate() {
+                    cells.insert((row + i as u32, col + j as u32), v.clone());
+                }
+            }
+        }
+        Value::Array(items) => {
+            let down = range.start.1 == range.end.1 && range.end.0 > range.start.0;
+            for (i, v) in items.iter().enumerate() {
+                let cell = if down {
+                    (row + i as u32, col)
+                } else {
+                    (row, col + i as u32)
+                };
+                cells.insert(cell, v.clone());
+            }
+        }
+        _ => {
+            cells.insert(range.start, value.clone());
+        }
+    }
+}
+
+/// A template with its values filled in, not yet written out
+struct FilledTemplate {
+    archive: ZipArchive<BufReader<File>>,
+    /// new content of the rewritten parts
+    changed: HashMap<String, Vec<u8>>,
+    has_chain: bool,
+    report: FillReport,
+}
+
+fn fill(src: &str, dst: &str, values: &JsonObject) -> anyhow::Result<FilledTemplate> {
+    let template = Path::new(src)
+        .canonicalize()
+        .with_context(|| format!("template not found: {}", src))?;
+    // a missing `dst` can't be the template, canonicalize fails for it
+    if Path::new(dst)
+        .canonicalize()
+        .is_ok_and(|dst| dst == template)
+    {
+        bail!("the filled workbook must be saved to a new path: {}", dst);
+    }
+    let file = File::open(src).with_context(|| format!("failed to open template: {}", src))?;
+    let mut archive = ZipArchive::new(BufReader::new(file))
+        .map_err(|e| anyhow!("invalid xlsx template {}: {}", src, e))?;
+
+    let workbook_xml = read_entry(&mut archive, "xl/workbook.xml")?;
+    let rels = read_entry(&mut archive, "xl/_rels/workbook.xml.rels")?;
+    let parts = parse_workbook(&workbook_xml, &rels)?;
+    let shared = if archive.index_for_name("xl/sharedStrings.xml").is_some() {
+        parse_shared_strings(&read_entry(&mut archive, "xl/sharedStrings.xml")?)?
+    } else {
+        vec![]
+    };
+
+    // cells set through defined names, per worksheet part
+    let mut named: HashMap<&str, BTreeMap<Cell, Value>> = HashMap::new();
+    for (key, value) in values {
+        let Some((_, formula)) = parts
+            .names
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(key))
+        else {
+            continue;
+        };
+        let range = match A1Range::parse(formula.trim_start_matches('=')) {
+            Ok(r) if r.sheet.is_some() => r,
+            _ => bail!(
+                "defined name {} does not refer to a cell range: {}",
+                key,
+                formula
+            ),
+        };
+        let sheet = range.sheet.as_deref().unwrap_or_default();
+        let Some(part) = parts.sheets.iter().find(|s| s.name == sheet) else {
+            bail!("defined name {} refers to a missing sheet: {}", key, sheet);
+        };
+        named_cells(&range, value, named.entry(part.path.as_str()).or_default());
+    }
+
+    let mut report = FillReport::default();
+    let mut changed: HashMap<String, Vec<u8>> = HashMap::new();
+    for part in &parts.sheets {
+        let xml = read_entry(&mut archive, &part.path)?;
+        let cells = named.remove(part.path.as_str()).unwrap_or_default();
+        if let Some(xml) = fill_sheet(&xml, cells, &shared, values, &mut report)
+            .with_context(|| format!("failed to fill sheet {}", part.name))?
+        {
+            changed.insert(part.path.clone(), xml);
+        }
+    }
+
+    let has_chain = archive.index_for_name("xl/calcChain.xml").is_some();
+    if !changed.is_empty() {
+        changed.insert("xl/workbook.xml".to_string(), force_recalc(&workbook_xml)?);
+        if has_chain {
+            changed.insert(
+                "xl/_rels/workbook.xml.rels".to_string(),
+                drop_calc_chain(&rels, b"Relationship", b"Target")?,
+            );
+            let types = read_entry(&mut archive, "[Content_Types].xml")?;
+            changed.insert(
+                "[Content_Types].xml".to_string(),
+                drop_calc_chain(&types, b"Override", b"PartName")?,
+            );
+        }
+    }
+
+    Ok(FilledTemplate {
+        archive,
+        changed,
+        has_chain,
+        report,
+    })
+}
+
+impl FilledTemplate {
+    /// write the package to `out`, the parts that weren't changed are copied as is
+    fn write<W: Write + Seek>(mut self, out: W) -> anyhow::Result<(FillReport, W)> {
+        let archive = &mut self.archive;
+        let changed = &self.changed;
+        let has_chain = self.has_chain;
+        let mut zip = ZipWriter::new(out);
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        for i in 0..archive.len() {
+            let entry = archive.by_index_raw(i)?;
+            let name = entry.name().to_string();
+            if let Some(xml) = changed.get(&name) {
+                drop(entry);
+                zip.start_file(name.as_str(), options)?;
+                zip.write_all(xml)?;
+            } else if has_chain && !changed.is_empty() && name == "xl/calcChain.xml" {
+                continue;
+            } else {
+                zip.raw_copy_file(entry)?;
+            }
+        }
+        let out = zip.finish()?;
+        Ok((self.report, out))
+    }
+}
+
+/// Fill the xlsx template at `src`, returns the report and the bytes of the
+/// workbook to save to `dst`. Keys of `values` matching a defined name set the
+/// cells of that name, `{{key}}` placeholders in cell text are replaced
+/// anywhere in the workbook. Only the changed worksheets are rewritten, every
+/// other part of the package is copied as is.
+pub fn fill_template(
+    src: &str,
+    dst: &str,
+    values: &JsonObject,
+) -> anyhow::Result<(FillReport, Vec<u8>)> {
+    let (report, out) = fill(src, dst, values)?.write(Cursor::new(Vec::new()))?;
+    Ok((report, out.into_inner()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn fill_placeholder_text() {
+        let values = json!({"total": 12.5, "order": {"id": "A-7"}});
+        let values = values.as_object().unwrap();
+        let mut missing = vec![];
+
+        assert_eq!(
+            fill_text(" {{ total }} ", values, &mut missing),
+            Some(json!(12.5))
+        );
+        assert_eq!(
+            fill_text(
+                "Order {{order.id}}: {{total}} {{note}}",
+                values,
+                &mut missing
+            ),
+            Some(json!("Order A-7: 12.5 {{note}}"))
+        );
+        assert_eq!(fill_text("no placeholder", values, &mut missing), None);
+        assert_eq!(fill_text("{{note}}", values, &mut missing), None);
+        assert_eq!(missing, vec!["note".to_string()]);
+    }
+
+    #[test]
+    fn fill_sheet_cells() {
+        let xml = br#"<worksheet><dimension ref="A2:C2"/><sheetData><row r="2"><c r="A2" s="3" t="s"><v>0</v></c><c r="C2" s="4"><v>1</v></c></row></sheetData></worksheet>"#;
+        let shared = vec!["{{name}}".to_string()];
+        let values = json!({"name": "Ann"});
+        let mut named = BTreeMap::new();
+        named.insert((1, 1), json!(5));
+        named.insert((3, 0), json!(true));
+        let mut report = FillReport::default();
+
+        let out = fill_sheet(
+            xml,
+            named,
+            &shared,
+            values.as_object().unwrap(),
+            &mut report,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            r#"<worksheet><dimension ref="A2:C4"/><sheetData><row r="2"><c r="A2" s="3" t="inlineStr"><is><t xml:space="preserve">Ann</t></is></c><c r="B2"><v>5</v></c><c r="C2" s="4"><v>1</v></c></row><row r="4"><c r="A4" t="b"><v>1</v></c></row></sheetData></worksheet>"#
+        );
+        assert_eq!(report.cells, 3);
+    }
+
+    #[test]
+    fn reject_missing_template() {
+        let values = JsonObject::new();
+        let err = fill_template("missing.xlsx", "missing.xlsx", &values)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(err, "template not found: missing.xlsx");
+    }
+}
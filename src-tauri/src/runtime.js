@@ -17,12 +17,273 @@
     return core.ops.op_snowid(arg);
   }
 
+  globalThis.column_name = (index) => {
+    return core.ops.op_column_name(index);
+  }
+
+  globalThis.column_index = (name) => {
+    return core.ops.op_column_index(name);
+  }
+
   globalThis.Handlebars = {
     render: (template, data) => {
       return core.ops.handlebars_render(template, data);
     }
   }
 
+  function workbook(sheets) {
+    return {
+      sheets,
+      sheet_names: () => sheets.map((s) => s.name),
+      sheet: (nameOrIndex) => {
+        if (typeof nameOrIndex === "number") {
+          return sheets[nameOrIndex];
+        }
+        return sheets.find((s) => s.name === nameOrIndex);
+      },
+    };
+  }
+
+  function readOptions(options) {
+    if (typeof options === "number") {
+      return { sheet: options };
+    }
+    if (typeof options === "string") {
+      return options.includes("!") ? { range: options } : { sheet: options };
+    }
+    return options ?? {};
+  }
+
+  function reviveDates(value) {
+    if (Array.isArray(value)) {
+      return value.map(reviveDates);
+    }
+    if (value !== null && typeof value === "object") {
+      if (typeof value.$date === "string" && Object.keys(value).length === 1) {
+        return new Date(value.$date);
+      }
+      for (const key of Object.keys(value)) {
+        value[key] = reviveDates(value[key]);
+      }
+    }
+    return value;
+  }
+
+  function reviveSheet(sheet, options) {
+    if (options.dates === "date") {
+      sheet.rows = reviveDates(sheet.rows);
+    }
+    return sheet;
+  }
+
+  // local date time without zone, the same form read_sheet returns
+  function localIso(date) {
+    const pad = (n, len = 2) => String(n).padStart(len, "0");
+    return `${date.getFullYear()}-${pad(date.getMonth() + 1)}-${pad(date.getDate())}` +
+      `T${pad(date.getHours())}:${pad(date.getMinutes())}:${pad(date.getSeconds())}` +
+      `.${pad(date.getMilliseconds(), 3)}`;
+  }
+
+  function toCell(value) {
+    if (value instanceof Date) {
+      return { $date: localIso(value) };
+    }
+    return value === undefined ? null : value;
+  }
+
+  // Uint8Array view of typed arrays, ArrayBuffers and arrays of byte values
+  function toBytes(data) {
+    if (data instanceof Uint8Array) {
+      return data;
+    }
+    if (ArrayBuffer.isView(data)) {
+      return new Uint8Array(data.buffer, data.byteOffset, data.byteLength);
+    }
+    return new Uint8Array(data);
+  }
+
+  // a single include or exclude pattern may be passed as a string
+  function walkOptions(options) {
+    const out = { ...(options ?? {}) };
+    for (const key of ["include", "exclude"]) {
+      if (typeof out[key] === "string") {
+        out[key] = [out[key]];
+      }
+    }
+    return out;
+  }
+
+  // times are sent as milliseconds since the epoch
+  function reviveEntry(entry) {
+    for (const key of ["created", "modified", "accessed"]) {
+      if (key in entry) {
+        entry[key] = entry[key] === null ? null : new Date(entry[key]);
+      }
+    }
+    return entry;
+  }
+
+  // column number, letters such as "C" or a range such as "A:C" to [first, last]
+  function columnRange(col) {
+    if (typeof col === "number") {
+      return [col, col];
+    }
+    const [first, last] = String(col).split(":");
+    return [core.ops.op_column_index(first), core.ops.op_column_index(last ?? first)];
+  }
+
+  // dates anywhere in the values, including nested objects and arrays
+  function templateValue(value) {
+    if (Array.isArray(value)) {
+      return value.map(templateValue);
+    }
+    if (value !== null && typeof value === "object" && !(value instanceof Date) &&
+      !("formula" in value) && !("$date" in value)) {
+      const out = {};
+      for (const [key, v] of Object.entries(value)) {
+        out[key] = templateValue(v);
+      }
+      return out;
+    }
+    return toCell(value);
+  }
+
+  function worksheet(rid, index, name) {
+    let nextRow = 0;
+    const write = (row, col, data, style) => {
+      core.ops.op_xlsx_write_rows(rid, index, row, col, data, style ?? null);
+      nextRow = Math.max(nextRow, row + data.length);
+    };
+    const sheet = {
+      index,
+      name,
+      write: (row, col, value, style) => {
+        write(row, col, [[toCell(value)]], style);
+        return sheet;
+      },
+      write_row: (row, values, col = 0, style) => {
+        write(row, col, [values.map(toCell)], style);
+        return sheet;
+      },
+      append_row: (values, style) => sheet.write_row(nextRow, values, 0, style),
+      write_rows: (rows, options) => {
+        const { row = nextRow, col = 0, header, style, headerStyle, autofilter } = options ?? {};
+        let keys = null;
+        let data = rows;
+        if (rows.length > 0 && !Array.isArray(rows[0])) {
+          keys = Array.isArray(header) ? header : Object.keys(rows[0]);
+          data = rows.map((r) => keys.map((k) => r[k]));
+        }
+        let first = row;
+        if (keys !== null && header !== false) {
+          write(first, col, [keys], headerStyle);
+          first += 1;
+        }
+        write(first, col, data.map((values) => values.map(toCell)), style);
+        if (autofilter && first > row && data.length > 0) {
+          const last = col + keys.length - 1;
+          sheet.autofilter(
+            `${core.ops.op_column_name(col)}${row + 1}:${core.ops.op_column_name(last)}${first + data.length}`,
+          );
+        }
+        return sheet;
+      },
+      set_column: (col, options) => {
+        const [first, last] = columnRange(col);
+        core.ops.op_xlsx_set_columns(rid, index, first, last, options ?? {});
+        return sheet;
+      },
+      set_row: (row, options) => {
+        core.ops.op_xlsx_set_row(rid, index, row, options ?? {});
+        return sheet;
+      },
+      freeze_panes: (row, col = 0) => {
+        core.ops.op_xlsx_freeze_panes(rid, index, row, col);
+        return sheet;
+      },
+      autofilter: (range) => {
+        core.ops.op_xlsx_autofilter(rid, index, range);
+        return sheet;
+      },
+    };
+    return sheet;
+  }
+
+  globalThis.xlsx = {
+    workbook: () => {
+      const rid = core.ops.op_xlsx_new();
+      const sheets = [];
+      return {
+        sheets,
+        add_sheet: (name) => {
+          const index = core.ops.op_xlsx_add_sheet(rid, name ?? "");
+          const sheet = worksheet(rid, index, name ?? `Sheet${index + 1}`);
+          sheets.push(sheet);
+          return sheet;
+        },
+        save: (path) => {
+          return core.ops.op_xlsx_save(rid, path);
+        },
+        close: () => {
+          return core.ops.op_xlsx_close(rid);
+        },
+      };
+    },
+    formula: (formula, value) => {
+      return value === undefined ? { formula } : { formula, value };
+    },
+    fill_template: (src, dst, values) => {
+      const cells = {};
+      for (const [key, value] of Object.entries(values ?? {})) {
+        cells[key] = templateValue(value);
+      }
+      return core.ops.op_xlsx_fill_template(src ?? "", dst, cells);
+    },
+  };
+
+  globalThis.path = {
+    sep: core.ops.op_path_sep(),
+    join: (...parts) => {
+      return core.ops.op_path_join(parts.map(String));
+    },
+    resolve: (...parts) => {
+      return core.ops.op_path_resolve(parts.map(String));
+    },
+    normalize: (path) => {
+      return core.ops.op_path_normalize(path);
+    },
+    dirname: (path) => {
+      return core.ops.op_path_dirname(path);
+    },
+    basename: (path) => {
+      return core.ops.op_path_basename(path);
+    },
+    extname: (path) => {
+      return core.ops.op_path_extname(path);
+    },
+    stem: (path) => {
+      return core.ops.op_path_stem(path);
+    },
+    relative: (from, to) => {
+      return core.ops.op_path_relative(from, to);
+    },
+    home_dir: () => {
+      return core.ops.op_path_user_dir("home");
+    },
+    desktop_dir: () => {
+      return core.ops.op_path_user_dir("desktop");
+    },
+    documents_dir: () => {
+      return core.ops.op_path_user_dir("documents");
+    },
+    downloads_dir: () => {
+      return core.ops.op_path_user_dir("downloads");
+    },
+    output_dir: () => {
+      return core.ops.op_path_output_dir();
+    },
+  };
+
   globalThis.console = {
     log: (...args) => {
       core.ops.println(`${args.toString()}`);
@@ -36,11 +297,71 @@
     read_xls: (path) => {
       return core.ops.op_read_xls(path);
     },
+    read_workbook: async (path, options) => {
+      options = options ?? {};
+      const sheets = await core.ops.op_read_xls_sheets(path ?? "", options);
+      return workbook(sheets.map((sheet) => reviveSheet(sheet, options)));
+    },
+    read_sheet: async (path, options) => {
+      options = readOptions(options);
+      const sheet = await core.ops.op_read_xls_sheet(path ?? "", options);
+      return reviveSheet(sheet, options);
+    },
+    read_table: async (path, name, options) => {
+      options = options ?? {};
+      const table = await core.ops.op_read_xls_table(path ?? "", name, options);
+      return reviveSheet(table, options);
+    },
+    read_csv: async (path, options) => {
+      options = options ?? {};
+      const sheet = await core.ops.op_read_csv(path ?? "", options);
+      return reviveSheet(sheet, options);
+    },
+    write_csv: (path, rows, options) => {
+      const { header, ...rest } = options ?? {};
+      let data = rows;
+      if (rows.length > 0 && !Array.isArray(rows[0])) {
+        const keys = Array.isArray(header) ? header : Object.keys(rows[0]);
+        data = rows.map((r) => keys.map((k) => r[k]));
+        if (header !== false && !(rest.append && core.ops.op_fs_exists(path))) {
+          data.unshift(keys);
+        }
+      }
+      return core.ops.op_write_csv(path, data.map((values) => values.map(toCell)), rest);
+    },
+    xls_tables: (path) => {
+      return core.ops.op_xls_tables(path ?? "");
+    },
+    xls_names: (path) => {
+      return core.ops.op_xls_defined_names(path ?? "");
+    },
+    stream_xls: async function* (path, options) {
+      const { chunkSize, chunks, ...rest } = readOptions(options);
+      const rid = core.ops.op_xls_stream_open(path ?? "", rest, chunkSize ?? 1000);
+      try {
+        while (true) {
+          let rows = await core.ops.op_xls_stream_next(rid);
+          if (rows === null) {
+            return;
+          }
+          if (rest.dates === "date") {
+            rows = reviveDates(rows);
+          }
+          if (chunks) {
+            yield rows;
+          } else {
+            yield* rows;
+          }
+        }
+      } finally {
+        core.ops.op_xls_stream_close(rid);
+      }
+    },
     create: (path) => {
       return core.ops.op_fs_create_file(path);
     },
-    append: (path, content) => {
-      return core.ops.op_fs_append(path, content);
+    append: (path, content, encoding) => {
+      return core.ops.op_fs_append(path, content, encoding ?? "");
     },
     copy: (src, dst) => {
       return core.ops.op_fs_copy_file(src, dst);
@@ -66,8 +387,8 @@
     hard_link: (src, dst) => {
       return core.ops.op_fs_hard_link(src, dst);
     },
-    read_to_string: (path) => {
-      return core.ops.op_fs_read_to_string(path);
+    read_to_string: (path, encoding) => {
+      return core.ops.op_fs_read_to_string(path, encoding ?? "");
     },
     remove_dir: (path) => {
       return core.ops.op_fs_remove_dir(path);
@@ -81,12 +402,108 @@
     rename: (src, dst) => {
       return core.ops.op_fs_rename(src, dst);
     },
-    write: (path, contents) => {
-      return core.ops.op_fs_write(path, contents);
+    write: (path, contents, encoding) => {
+      return core.ops.op_fs_write(path, contents, encoding ?? "");
+    },
+    read_to_line: (filePath, encoding) => {
+      return core.ops.op_fs_read_line(filePath, encoding ?? "");
+    },
+    read_bytes: (path) => {
+      return core.ops.op_fs_read_bytes(path);
+    },
+    read_range: (path, offset, length) => {
+      return core.ops.op_fs_read_range(path, offset, length);
+    },
+    write_bytes: (path, data) => {
+      return core.ops.op_fs_write_bytes(path, toBytes(data));
+    },
+    append_bytes: (path, data) => {
+      return core.ops.op_fs_append_bytes(path, toBytes(data));
+    },
+    walk: (root, options) => {
+      return core.ops.op_fs_walk(root, walkOptions(options)).map(reviveEntry);
+    },
+    glob: (pattern, options) => {
+      return core.ops.op_fs_glob(pattern, walkOptions(options)).map(reviveEntry);
+    },
+    stat: (path) => {
+      return reviveEntry(core.ops.op_fs_stat(path));
+    },
+    // the same api returning promises, so that many files can be read or written at once
+    promises: {
+      create: (path) => {
+        return core.ops.op_fs_create_file_async(path);
+      },
+      append: (path, content, encoding) => {
+        return core.ops.op_fs_append_async(path, content, encoding ?? "");
+      },
+      copy: (src, dst) => {
+        return core.ops.op_fs_copy_file_async(src, dst);
+      },
+      create_dir: (path) => {
+        return core.ops.op_fs_create_dir_async(path);
+      },
+      read_dir: (path) => {
+        return core.ops.op_fs_read_dir_async(path);
+      },
+      is_dir: (path) => {
+        return core.ops.op_fs_is_dir_async(path);
+      },
+      is_file: (path) => {
+        return core.ops.op_fs_is_file_async(path);
+      },
+      create_dir_all: (path) => {
+        return core.ops.op_fs_create_dir_all_async(path);
+      },
+      exists: (path) => {
+        return core.ops.op_fs_exists_async(path);
+      },
+      hard_link: (src, dst) => {
+        return core.ops.op_fs_hard_link_async(src, dst);
+      },
+      read_to_string: (path, encoding) => {
+        return core.ops.op_fs_read_to_string_async(path, encoding ?? "");
+      },
+      remove_dir: (path) => {
+        return core.ops.op_fs_remove_dir_async(path);
+      },
+      remove_dir_all: (path) => {
+        return core.ops.op_fs_remove_dir_all_async(path);
+      },
+      remove_file: (path) => {
+        return core.ops.op_fs_remove_file_async(path);
+      },
+      rename: (src, dst) => {
+        return core.ops.op_fs_rename_async(src, dst);
+      },
+      write: (path, contents, encoding) => {
+        return core.ops.op_fs_write_async(path, contents, encoding ?? "");
+      },
+      read_to_line: (filePath, encoding) => {
+        return core.ops.op_fs_read_line_async(filePath, encoding ?? "");
+      },
+      read_bytes: (path) => {
+        return core.ops.op_fs_read_bytes_async(path);
+      },
+      read_range: (path, offset, length) => {
+        return core.ops.op_fs_read_range_async(path, offset, length);
+      },
+      write_bytes: (path, data) => {
+        return core.ops.op_fs_write_bytes_async(path, toBytes(data));
+      },
+      append_bytes: (path, data) => {
+        return core.ops.op_fs_append_bytes_async(path, toBytes(data));
+      },
+      walk: async (root, options) => {
+        return (await core.ops.op_fs_walk_async(root, walkOptions(options))).map(reviveEntry);
+      },
+      glob: async (pattern, options) => {
+        return (await core.ops.op_fs_glob_async(pattern, walkOptions(options))).map(reviveEntry);
+      },
+      stat: async (path) => {
+        return reviveEntry(await core.ops.op_fs_stat_async(path));
+      },
     },
-    read_to_line: (filePath) => {
-      return core.ops.op_fs_read_line(filePath);
-    }
 
   };
 })(globalThis);
@@ -3,6 +3,12 @@ export interface FileInfo {
     xlxTemplate: string;
     name: string;
     code?: string;
+    timeoutSecs?: number | null;
+    maxHeapMb?: number | null;
+    outputDir?: string | null;
+    readDirs?: string | null;
+    writeDirs?: string | null;
+    promptAccess?: boolean | null;
     createdDate?: Date;
     updatedDate?: Date;
     selected?: boolean;
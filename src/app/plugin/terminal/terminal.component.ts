@@ -28,6 +28,9 @@ export class TerminalComponent implements OnInit {
   @Output()
   runClick: EventEmitter<String> = new EventEmitter();
 
+  @Output()
+  cancelClick: EventEmitter<String> = new EventEmitter();
+
   @ViewChild("content") content!: ElementRef;
 
   @ViewChild(CdkVirtualScrollViewport, { static: true }) scrollViewport!: CdkVirtualScrollViewport;
@@ -60,7 +63,7 @@ export class TerminalComponent implements OnInit {
       if (res.logType !== "result") {
         this.setAMsg(res);
       }
-      if (res.logType === "result" || res.logType === "error") {
+      if (res.logType === "result" || res.logType === "error" || res.logType === "cancelled") {
         this.running = false;
         // this.logSubject.next(uuidv4().toString())
         // this.message = [...this.message];
@@ -108,6 +111,16 @@ export class TerminalComponent implements OnInit {
     this.runClick.emit("run");
   }
 
+  async dryRun($event: MouseEvent) {
+    await this.clear($event);
+    this.running = true;
+    this.runClick.emit("dryRun");
+  }
+
+  async stop($event: MouseEvent) {
+    this.cancelClick.emit("cancel");
+  }
+
   async clear($event: MouseEvent) {
     this.message = [];
     this.messageProduct.next([]);
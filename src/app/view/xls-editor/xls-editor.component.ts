@@ -64,12 +64,20 @@ export class XlsEditorComponent implements AfterViewInit, OnInit {
 
     xlsId!: number;
 
+    runId?: string;
+
     fileList = Array<FileInfo>();
 
     fileForm = new FormGroup({
         id: new FormControl(''),
         name: new FormControl('', [Validators.required, Validators.minLength(4)]),
         xlxTemplate: new FormControl('', [Validators.required]),
+        timeoutSecs: new FormControl<number | null>(null, [Validators.min(1)]),
+        maxHeapMb: new FormControl<number | null>(null, [Validators.min(64)]),
+        outputDir: new FormControl<string | null>(null),
+        readDirs: new FormControl<string | null>(null),
+        writeDirs: new FormControl<string | null>(null),
+        promptAccess: new FormControl<boolean | null>(false),
     });
 
     messageSrv = inject(MessageService)
@@ -152,21 +160,42 @@ export class XlsEditorComponent implements AfterViewInit, OnInit {
             let add_form = {
                 id : fileForm.id,
                 name: fileForm.name as string,
-                xls: fileForm.xlxTemplate as string
+                xls: fileForm.xlxTemplate as string,
+                timeoutSecs: fileForm.timeoutSecs || null,
+                maxHeapMb: fileForm.maxHeapMb || null
             }    
-            const res = await invoke<FileInfo>("update_name_xls_by_id", {...add_form});
+            await invoke<FileInfo>("update_name_xls_by_id", {...add_form});
+            const res = await invoke<FileInfo>("update_permissions_by_id", {
+                id: fileForm.id,
+                outputDir: fileForm.outputDir || null,
+                readDirs: fileForm.readDirs || null,
+                writeDirs: fileForm.writeDirs || null,
+                promptAccess: fileForm.promptAccess || false
+            });
             this.fileList.forEach(x=>{
                 if(x.id === fileForm.id){
                     x.name  = res.name;
                     x.xlxTemplate  = res.xlxTemplate;
                     x.code  = res.code;
+                    x.timeoutSecs  = res.timeoutSecs;
+                    x.maxHeapMb  = res.maxHeapMb;
+                    x.outputDir  = res.outputDir;
+                    x.readDirs  = res.readDirs;
+                    x.writeDirs  = res.writeDirs;
+                    x.promptAccess  = res.promptAccess;
                 }
             })
         }else {
             let add_form = {
                 name: fileForm.name as string,
                 xlxTemplate: fileForm.xlxTemplate as string,
-                code: ""
+                code: "",
+                timeoutSecs: fileForm.timeoutSecs || null,
+                maxHeapMb: fileForm.maxHeapMb || null,
+                outputDir: fileForm.outputDir || null,
+                readDirs: fileForm.readDirs || null,
+                writeDirs: fileForm.writeDirs || null,
+                promptAccess: fileForm.promptAccess || false
             }    
             const res = await invoke<FileInfo>("add_file", {newFile: add_form});
             this.fileList.push(res);
@@ -180,8 +209,8 @@ export class XlsEditorComponent implements AfterViewInit, OnInit {
         const selected = await open({
             multiple: false,
             filters: [{
-                name: 'xlsx',
-                extensions: ['xlsx']
+                name: 'excel',
+                extensions: ['xlsx', 'xlsm', 'xlsb', 'xls', 'ods']
             }]
         });
         if (selected) {
@@ -191,6 +220,18 @@ export class XlsEditorComponent implements AfterViewInit, OnInit {
         }
     }
 
+    async selectOutputDirClick($event: MouseEvent) {
+        const selected = await open({
+            multiple: false,
+            directory: true
+        });
+        if (selected) {
+            this.fileForm.patchValue({
+                outputDir: selected as string
+            })
+        }
+    }
+
     fileClick($event: MouseEvent, filInfo: FileInfo) {
         filInfo.selected = true;
         this.xlsId = filInfo.id as number;
@@ -226,7 +267,15 @@ export class XlsEditorComponent implements AfterViewInit, OnInit {
        if(!fileInfo){
            return
        }
-       await invoke('run', {id: fileInfo.id});
+       this.runId = await invoke<string>('run', {id: fileInfo.id, dryRun: $event === 'dryRun'});
+    }
+
+    async cancelClick($event: String) {
+       if(!this.runId){
+           return
+       }
+       await invoke<boolean>('cancel_run', {runId: this.runId});
+       this.runId = undefined;
     }
 
 }